@@ -0,0 +1,167 @@
+// Copying input straight to stdout, for when no option needs to look at the lines.
+//
+// On Linux the kernel can move the data itself: copy_file_range between regular files, and splice
+// when either side is a pipe. Both avoid copying every byte through userspace. Anything they refuse
+// to handle (e.g. a terminal on stdout, or a file system without copy_file_range support) falls
+// back to reading and writing through a large buffer.
+
+use std::io::{self, Read, StdoutLock, Write};
+
+const BUFFER_SIZE: usize = 128 * 1024;
+
+// Which side of a copy failed. A read error only spoils the current input, whereas a write error
+// will spoil every input after it as well.
+#[derive(Debug)]
+pub enum CopyError {
+    Read(io::Error),
+    Write(io::Error),
+}
+
+#[cfg(target_os = "linux")]
+pub fn copy<R: Read + std::os::unix::io::AsRawFd>(
+    input: &mut R,
+    output: &mut StdoutLock,
+) -> Result<u64, CopyError> {
+    use std::os::unix::io::AsRawFd;
+
+    // Anything already buffered by the standard library has to go out before the kernel starts
+    // writing to the same file descriptor behind its back.
+    output.flush().map_err(CopyError::Write)?;
+
+    let (input_fd, output_fd) = (input.as_raw_fd(), output.as_raw_fd());
+    if let Some(copied) = linux::copy_file_range(input_fd, output_fd)? {
+        return Ok(copied);
+    }
+    if let Some(copied) = linux::splice(input_fd, output_fd)? {
+        return Ok(copied);
+    }
+    buffered_copy(input, output)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn copy<R: Read>(input: &mut R, output: &mut StdoutLock) -> Result<u64, CopyError> {
+    buffered_copy(input, output)
+}
+
+fn buffered_copy<R: Read>(input: &mut R, output: &mut StdoutLock) -> Result<u64, CopyError> {
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut copied = 0;
+
+    loop {
+        let bytes = match input.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(CopyError::Read(e)),
+        };
+        output
+            .write_all(&buffer[..bytes])
+            .map_err(CopyError::Write)?;
+        copied += bytes as u64;
+    }
+
+    output.flush().map_err(CopyError::Write)?;
+    Ok(copied)
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::CopyError;
+    use std::io;
+    use std::os::unix::io::RawFd;
+    use std::ptr;
+
+    // The most either syscall is asked to move at once; both return early at the end of the input.
+    const CHUNK_SIZE: usize = 1 << 30;
+
+    // Returns None, having copied nothing, when copy_file_range cannot be used for this pair of
+    // files, so that the caller can try something else.
+    pub fn copy_file_range(input: RawFd, output: RawFd) -> Result<Option<u64>, CopyError> {
+        // Files in e.g. /proc report a size of 0 and make copy_file_range return 0 straight away,
+        // which would look like an empty file, so only regular files with contents are tried.
+        if !is_regular_file(input, true).map_err(CopyError::Read)?
+            || !is_regular_file(output, false).map_err(CopyError::Write)?
+        {
+            return Ok(None);
+        }
+
+        transfer(|| unsafe {
+            libc::copy_file_range(
+                input,
+                ptr::null_mut(),
+                output,
+                ptr::null_mut(),
+                CHUNK_SIZE,
+                0,
+            )
+        })
+    }
+
+    // As with copy_file_range, None means that splice cannot be used and nothing was copied.
+    pub fn splice(input: RawFd, output: RawFd) -> Result<Option<u64>, CopyError> {
+        if !is_pipe(input).map_err(CopyError::Read)?
+            && !is_pipe(output).map_err(CopyError::Write)?
+        {
+            return Ok(None);
+        }
+
+        transfer(|| unsafe {
+            libc::splice(
+                input,
+                ptr::null_mut(),
+                output,
+                ptr::null_mut(),
+                CHUNK_SIZE,
+                libc::SPLICE_F_MOVE,
+            )
+        })
+    }
+
+    // The kernel does not say which side of the copy an error came from, so it is guessed from the
+    // error itself: the ones that can only come from writing are blamed on the output.
+    fn transfer(mut syscall: impl FnMut() -> isize) -> Result<Option<u64>, CopyError> {
+        let mut copied = 0;
+
+        loop {
+            match syscall() {
+                0 => return Ok(Some(copied)),
+                bytes if bytes > 0 => copied += bytes as u64,
+                _ => {
+                    let e = io::Error::last_os_error();
+                    match e.raw_os_error() {
+                        Some(libc::EINTR) => continue,
+                        Some(
+                            libc::EINVAL
+                            | libc::EXDEV
+                            | libc::ENOSYS
+                            | libc::EOPNOTSUPP
+                            | libc::EBADF
+                            | libc::EPERM,
+                        ) if copied == 0 => return Ok(None),
+                        Some(libc::EPIPE | libc::ENOSPC | libc::EDQUOT | libc::EFBIG) => {
+                            return Err(CopyError::Write(e))
+                        }
+                        _ => return Err(CopyError::Read(e)),
+                    }
+                }
+            }
+        }
+    }
+
+    fn stat(fd: RawFd) -> io::Result<libc::stat> {
+        let mut stat = unsafe { std::mem::zeroed::<libc::stat>() };
+        if unsafe { libc::fstat(fd, &mut stat) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(stat)
+    }
+
+    fn is_regular_file(fd: RawFd, needs_contents: bool) -> io::Result<bool> {
+        let stat = stat(fd)?;
+        Ok(stat.st_mode & libc::S_IFMT == libc::S_IFREG && (!needs_contents || stat.st_size > 0))
+    }
+
+    fn is_pipe(fd: RawFd) -> io::Result<bool> {
+        Ok(stat(fd)?.st_mode & libc::S_IFMT == libc::S_IFIFO)
+    }
+}
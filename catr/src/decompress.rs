@@ -0,0 +1,78 @@
+// Decompressing input for --decompress.
+//
+// The format is worked out from the magic bytes at the start of the input rather than from the
+// file name, so that compressed data on stdin, or a rotated log without the right extension, is
+// still recognised. Anything that does not start with a known magic number is passed through as it
+// is, as zcat -f does. Nothing here is specific to catr: it only turns one reader into another.
+
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+
+// Long enough for the longest magic number below (bzip2, with the block magic after it).
+const MAGIC_LENGTH: usize = 10;
+
+// What comes after bzip2's "BZh" and block size: the magic of the first block, or of the end of
+// the stream if there are no blocks, as for an empty file. "BZh" alone is too likely in plain text.
+const BZIP2_BLOCK_MAGIC: [u8; 6] = [0x31, 0x41, 0x59, 0x26, 0x53, 0x59];
+const BZIP2_END_MAGIC: [u8; 6] = [0x17, 0x72, 0x45, 0x38, 0x50, 0x90];
+
+#[derive(Debug, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    pub fn detect(start: &[u8]) -> Option<Self> {
+        if start.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if is_bzip2(start) {
+            Some(Compression::Bzip2)
+        } else if start.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else if start.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+fn is_bzip2(start: &[u8]) -> bool {
+    match start {
+        [b'B', b'Z', b'h', b'1'..=b'9', magic @ ..] if magic.len() >= 6 => {
+            magic[..6] == BZIP2_BLOCK_MAGIC || magic[..6] == BZIP2_END_MAGIC
+        }
+        _ => false,
+    }
+}
+
+// Wraps `input` in the decompressor its first few bytes call for, if any. Concatenated streams
+// (e.g. `cat a.gz b.gz`) are decompressed one after another, as the command line tools do.
+pub fn open<'a>(mut input: impl Read + 'a) -> io::Result<Box<dyn BufRead + 'a>> {
+    // A pipe can hand over less than was asked for, so keep reading until the magic number is
+    // complete or the input ends.
+    let mut start = Vec::with_capacity(MAGIC_LENGTH);
+    (&mut input)
+        .take(MAGIC_LENGTH as u64)
+        .read_to_end(&mut start)?;
+    let compression = Compression::detect(&start);
+    let input = BufReader::new(Cursor::new(start).chain(input));
+
+    Ok(match compression {
+        None => Box::new(input),
+        Some(Compression::Gzip) => {
+            Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(input)))
+        }
+        Some(Compression::Bzip2) => {
+            Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(input)))
+        }
+        Some(Compression::Xz) => Box::new(BufReader::new(
+            xz2::bufread::XzDecoder::new_multi_decoder(input),
+        )),
+        Some(Compression::Zstd) => Box::new(BufReader::new(
+            zstd::stream::read::Decoder::with_buffer(input)?,
+        )),
+    })
+}
@@ -0,0 +1,238 @@
+// Working out and normalising the text encoding of the input, for --detect-encoding, --strip-bom
+// and --to-utf8.
+//
+// A byte order mark settles the encoding straight away. Without one, UTF-16 is recognised by its
+// zero bytes, which every ASCII character has in the same half of its code unit, and anything else
+// is UTF-8 if it is valid UTF-8 and Latin-1 (where every byte is a character) if not.
+
+use std::fmt;
+use std::io::{self, BufRead, Cursor, Read};
+
+// How much of the start of the input is looked at for a byte order mark or UTF-16 zero bytes.
+const SAMPLE_LENGTH: usize = 4096;
+
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+const UTF16LE_BOM: &[u8] = b"\xff\xfe";
+const UTF16BE_BOM: &[u8] = b"\xfe\xff";
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Encoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf8Bom => "UTF-8 with BOM",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Latin1 => "Latin-1",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// Only tells apart what can be seen from the start of the input, so anything it does not take for
+// UTF-16 or UTF-8 with a byte order mark comes back as plain UTF-8.
+fn detect_start(start: &[u8]) -> Encoding {
+    if start.starts_with(UTF8_BOM) {
+        return Encoding::Utf8Bom;
+    }
+    if start.starts_with(UTF16LE_BOM) {
+        return Encoding::Utf16Le;
+    }
+    if start.starts_with(UTF16BE_BOM) {
+        return Encoding::Utf16Be;
+    }
+
+    // The zero bytes have to be mostly in one half of each pair and hardly ever in the other.
+    let units = start.len() / 2;
+    let zeros = |half: usize| start.chunks_exact(2).filter(|unit| unit[half] == 0).count();
+    let (first_zeros, second_zeros) = (zeros(0), zeros(1));
+    if second_zeros * 2 > units && first_zeros * 10 < units {
+        Encoding::Utf16Le
+    } else if first_zeros * 2 > units && second_zeros * 10 < units {
+        Encoding::Utf16Be
+    } else {
+        Encoding::Utf8
+    }
+}
+
+// Reads the whole of `input`, as a single byte anywhere can rule out UTF-8.
+pub fn detect(input: &mut impl Read) -> io::Result<Encoding> {
+    let mut buffer = vec![0; 64 * 1024];
+    let mut unchecked = Vec::new();
+    let mut first = true;
+
+    loop {
+        let bytes = match input.read(&mut buffer) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        unchecked.extend_from_slice(&buffer[..bytes]);
+
+        if first && (unchecked.len() >= SAMPLE_LENGTH || bytes == 0) {
+            first = false;
+            match detect_start(&unchecked) {
+                Encoding::Utf8 => {}
+                encoding => return Ok(encoding),
+            }
+        }
+        if first {
+            continue;
+        }
+
+        // A character cut in half by the end of what has been read so far is checked once the
+        // rest of it has been read.
+        match std::str::from_utf8(&unchecked) {
+            Ok(_) => unchecked.clear(),
+            Err(e) if e.error_len().is_none() && bytes != 0 => {
+                unchecked.drain(..e.valid_up_to());
+            }
+            Err(_) => return Ok(Encoding::Latin1),
+        }
+
+        if bytes == 0 {
+            return Ok(Encoding::Utf8);
+        }
+    }
+}
+
+// Wraps `input` so that it leaves out a UTF-8 byte order mark, if `strip_bom`, and comes out as
+// UTF-8 if it is UTF-16, if `to_utf8`. A UTF-16 byte order mark is always left out when
+// converting, as it would mean nothing once the text is UTF-8.
+pub fn normalise<'a>(
+    mut input: Box<dyn BufRead + 'a>,
+    strip_bom: bool,
+    to_utf8: bool,
+) -> io::Result<Box<dyn BufRead + 'a>> {
+    let mut start = Vec::with_capacity(SAMPLE_LENGTH);
+    (&mut input)
+        .take(SAMPLE_LENGTH as u64)
+        .read_to_end(&mut start)?;
+    let encoding = detect_start(&start);
+
+    let mut start = Cursor::new(start);
+    let bom = match encoding {
+        Encoding::Utf8Bom if strip_bom => UTF8_BOM.len(),
+        Encoding::Utf16Le if to_utf8 && start.get_ref().starts_with(UTF16LE_BOM) => 2,
+        Encoding::Utf16Be if to_utf8 && start.get_ref().starts_with(UTF16BE_BOM) => 2,
+        _ => 0,
+    };
+    start.set_position(bom as u64);
+    let input = start.chain(input);
+
+    Ok(match encoding {
+        Encoding::Utf16Le if to_utf8 => Box::new(Utf16Decoder::new(input, false)),
+        Encoding::Utf16Be if to_utf8 => Box::new(Utf16Decoder::new(input, true)),
+        _ => Box::new(io::BufReader::new(input)),
+    })
+}
+
+// Turns UTF-16 into UTF-8 as it is read. Anything that is not valid UTF-16 (an unpaired surrogate,
+// or an odd byte at the very end) comes out as U+FFFD.
+pub struct Utf16Decoder<R> {
+    input: R,
+    big_endian: bool,
+    // Bytes read but not decoded yet, because they are not a whole character.
+    undecoded: Vec<u8>,
+    output: Vec<u8>,
+    consumed: usize,
+}
+
+impl<R: Read> Utf16Decoder<R> {
+    pub fn new(input: R, big_endian: bool) -> Self {
+        Utf16Decoder {
+            input,
+            big_endian,
+            undecoded: Vec::new(),
+            output: Vec::new(),
+            consumed: 0,
+        }
+    }
+
+    // Leaves `output` empty only once the input has run out.
+    fn fill(&mut self) -> io::Result<()> {
+        self.output.clear();
+        self.consumed = 0;
+
+        let mut buffer = [0; 8192];
+        while self.output.is_empty() {
+            let bytes = match self.input.read(&mut buffer) {
+                Ok(bytes) => bytes,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            self.undecoded.extend_from_slice(&buffer[..bytes]);
+            let end_of_input = bytes == 0;
+
+            let mut units: Vec<u16> = self
+                .undecoded
+                .chunks_exact(2)
+                .map(|unit| match self.big_endian {
+                    true => u16::from_be_bytes([unit[0], unit[1]]),
+                    false => u16::from_le_bytes([unit[0], unit[1]]),
+                })
+                .collect();
+            let mut decoded = units.len() * 2;
+            // The first half of a surrogate pair waits for the second half.
+            if !end_of_input
+                && units
+                    .last()
+                    .is_some_and(|unit| (0xd800..0xdc00).contains(unit))
+            {
+                units.pop();
+                decoded -= 2;
+            }
+            self.undecoded.drain(..decoded);
+
+            let mut character = [0; 4];
+            for decoded in char::decode_utf16(units) {
+                let decoded = decoded.unwrap_or(char::REPLACEMENT_CHARACTER);
+                self.output
+                    .extend_from_slice(decoded.encode_utf8(&mut character).as_bytes());
+            }
+
+            if end_of_input {
+                if !self.undecoded.is_empty() {
+                    self.undecoded.clear();
+                    self.output.extend_from_slice(
+                        char::REPLACEMENT_CHARACTER
+                            .encode_utf8(&mut character)
+                            .as_bytes(),
+                    );
+                }
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Utf16Decoder<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let bytes = self.fill_buf()?.read(buffer)?;
+        self.consume(bytes);
+        Ok(bytes)
+    }
+}
+
+impl<R: Read> BufRead for Utf16Decoder<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.consumed == self.output.len() {
+            self.fill()?;
+        }
+        Ok(&self.output[self.consumed..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.consumed = (self.consumed + amount).min(self.output.len());
+    }
+}
@@ -0,0 +1,213 @@
+// Following regular files once they have been read to the end, for --follow.
+//
+// Every followed file is checked whenever the watcher wakes up. Waking up is only a hint that
+// something may have changed, so a spurious wake up costs a few stat calls and nothing else: on
+// Linux the watcher waits on inotify events for the directories holding the files, and elsewhere
+// (or if inotify cannot be set up) it wakes up every POLL_INTERVAL.
+
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// What happened to a followed file since it was last read.
+pub enum Change {
+    Truncated,
+    Replaced,
+}
+
+pub struct Followed {
+    name: String,
+    file: File,
+}
+
+impl Followed {
+    // `file` may still be being read from through another handle sharing its position, and is
+    // followed from wherever that leaves it. Anything other than a regular file is not followed,
+    // so None is returned for it.
+    pub fn new(name: &str, file: File) -> io::Result<Option<Self>> {
+        if !file.metadata()?.is_file() {
+            return Ok(None);
+        }
+
+        Ok(Some(Followed {
+            name: name.to_string(),
+            file,
+        }))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.file.read(buffer)
+    }
+
+    // Should only be called once everything has been read from the file, as a replaced file is
+    // let go of straight away.
+    // A file that is shorter than what has been read from it has been truncated, and is read again
+    // from the start. A name that now belongs to a different file (e.g. after log rotation) has
+    // that file opened in place of the old one. If the name has gone, the old file is kept until
+    // something new appears under it.
+    pub fn check(&mut self) -> io::Result<Option<Change>> {
+        let metadata = self.file.metadata()?;
+
+        if metadata.len() < self.file.stream_position()? {
+            self.file.seek(SeekFrom::Start(0))?;
+            return Ok(Some(Change::Truncated));
+        }
+
+        match fs::metadata(&self.name) {
+            Ok(current) if current.is_file() && !same_file(&current, &metadata) => {
+                self.file = File::open(&self.name)?;
+                Ok(Some(Change::Replaced))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn same_file(a: &Metadata, b: &Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+// Without inode numbers there is no telling one file from another, so replacement goes unnoticed.
+#[cfg(not(unix))]
+fn same_file(_: &Metadata, _: &Metadata) -> bool {
+    true
+}
+
+pub struct Watcher {
+    #[cfg(target_os = "linux")]
+    inotify: Option<linux::Inotify>,
+}
+
+impl Watcher {
+    pub fn new(followed: &[Followed]) -> Self {
+        #[cfg(target_os = "linux")]
+        {
+            Watcher {
+                inotify: linux::Inotify::new(followed.iter().map(Followed::name)).ok(),
+            }
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = followed;
+            Watcher {}
+        }
+    }
+
+    // Blocks until one of the followed files may have changed, or POLL_INTERVAL has passed.
+    pub fn wait(&mut self) {
+        #[cfg(target_os = "linux")]
+        if let Some(inotify) = &mut self.inotify {
+            if inotify.wait(POLL_INTERVAL).is_ok() {
+                return;
+            }
+            self.inotify = None;
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::collections::HashSet;
+    use std::ffi::CString;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::io::RawFd;
+    use std::path::Path;
+    use std::time::Duration;
+
+    // Watching the directories rather than the files themselves means that a file which is
+    // rotated away and replaced is still watched, and that its replacement appearing wakes us up.
+    const MASK: u32 = libc::IN_MODIFY
+        | libc::IN_ATTRIB
+        | libc::IN_CLOSE_WRITE
+        | libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO;
+
+    pub struct Inotify {
+        fd: RawFd,
+    }
+
+    impl Inotify {
+        pub fn new<'a>(names: impl Iterator<Item = &'a str>) -> io::Result<Self> {
+            let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+            if fd == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            let inotify = Inotify { fd };
+
+            let directories: HashSet<&Path> = names
+                .map(|name| match Path::new(name).parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent,
+                    _ => Path::new("."),
+                })
+                .collect();
+            for directory in directories {
+                let directory = CString::new(directory.as_os_str().as_bytes())?;
+                if unsafe { libc::inotify_add_watch(fd, directory.as_ptr(), MASK) } == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+
+            Ok(inotify)
+        }
+
+        // Waits for at least one event, or for `timeout` to pass, and then throws the events away:
+        // only the fact that something happened matters.
+        pub fn wait(&mut self, timeout: Duration) -> io::Result<()> {
+            let mut poll_fd = libc::pollfd {
+                fd: self.fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            if unsafe { libc::poll(&mut poll_fd, 1, timeout.as_millis() as libc::c_int) } == -1 {
+                let e = io::Error::last_os_error();
+                if e.kind() != io::ErrorKind::Interrupted {
+                    return Err(e);
+                }
+            }
+
+            let mut events = [0u8; 4096];
+            loop {
+                let bytes = unsafe {
+                    libc::read(
+                        self.fd,
+                        events.as_mut_ptr() as *mut libc::c_void,
+                        events.len(),
+                    )
+                };
+                match bytes {
+                    0 => return Ok(()),
+                    bytes if bytes > 0 => continue,
+                    _ => {
+                        let e = io::Error::last_os_error();
+                        match e.kind() {
+                            io::ErrorKind::WouldBlock => return Ok(()),
+                            io::ErrorKind::Interrupted => continue,
+                            _ => return Err(e),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    impl Drop for Inotify {
+        fn drop(&mut self) {
+            unsafe { libc::close(self.fd) };
+        }
+    }
+}
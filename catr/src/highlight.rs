@@ -0,0 +1,133 @@
+// Syntax highlighting for --highlight.
+//
+// Each input gets its own Highlighter, as the parser state (e.g. being inside a block comment)
+// belongs to the file being read. The language comes from --highlight=LANG if it was given, and
+// otherwise from the file's extension or name, then from its first line (e.g. a shebang), falling
+// back on plain text. Highlighting only ever adds escape sequences around the contents of a line:
+// working out what those contents are is left to the caller.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Style, Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+const THEME: &str = "base16-ocean.dark";
+
+// Compressed files are named after what they hold, so the extension underneath is the one that
+// says what the contents are.
+const COMPRESSION_EXTENSIONS: [&str; 4] = ["gz", "bz2", "xz", "zst"];
+
+pub const RESET: &[u8] = b"\x1b[0m";
+
+pub struct Highlighting {
+    syntaxes: SyntaxSet,
+    theme: Theme,
+    language: Option<String>,
+}
+
+// The syntax and theme definitions are far too long to be worth printing.
+impl fmt::Debug for Highlighting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Highlighting")
+            .field("language", &self.language)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Highlighting {
+    pub fn new(language: Option<&str>) -> Result<Self, String> {
+        let syntaxes = SyntaxSet::load_defaults_newlines();
+        if let Some(language) = language {
+            if syntaxes.find_syntax_by_token(language).is_none() {
+                return Err(format!("unknown language '{language}'"));
+            }
+        }
+
+        let theme = ThemeSet::load_defaults()
+            .themes
+            .remove(THEME)
+            .ok_or_else(|| format!("missing theme '{THEME}'"))?;
+
+        Ok(Highlighting {
+            syntaxes,
+            theme,
+            language: language.map(String::from),
+        })
+    }
+
+    // `first_line` is whatever is known of the start of the input, and may be empty.
+    pub fn for_input(&self, filename: &str, first_line: &str) -> Highlighter<'_> {
+        let syntax = match &self.language {
+            Some(language) => self.syntaxes.find_syntax_by_token(language),
+            None => self
+                .by_name(filename)
+                .or_else(|| self.syntaxes.find_syntax_by_first_line(first_line)),
+        }
+        .unwrap_or_else(|| self.syntaxes.find_syntax_plain_text());
+
+        Highlighter {
+            lines: HighlightLines::new(syntax, &self.theme),
+            syntaxes: &self.syntaxes,
+        }
+    }
+
+    fn by_name(&self, filename: &str) -> Option<&SyntaxReference> {
+        let mut path = Path::new(filename);
+        if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+            if COMPRESSION_EXTENSIONS.contains(&extension) {
+                path = Path::new(path.file_stem()?);
+            }
+        }
+
+        // Some syntaxes are picked by the whole file name rather than an extension (e.g. Makefile).
+        let extension = path.extension().or_else(|| path.file_name())?.to_str()?;
+        self.syntaxes.find_syntax_by_extension(extension)
+    }
+}
+
+pub struct Highlighter<'a> {
+    lines: HighlightLines<'a>,
+    syntaxes: &'a SyntaxSet,
+}
+
+impl<'a> Highlighter<'a> {
+    // Splits `line`, which includes its '\n' if it has one, into differently styled regions.
+    // Every line has to go through here, in order, to keep track of where the parser is; None is
+    // returned for those that cannot be highlighted, which are best written out as they are.
+    pub fn highlight<'l>(&mut self, line: &'l [u8]) -> Option<Vec<(Style, &'l [u8])>> {
+        match std::str::from_utf8(line) {
+            Ok(text) => {
+                let regions = self.lines.highlight_line(text, self.syntaxes).ok()?;
+                Some(
+                    regions
+                        .into_iter()
+                        .map(|(style, text)| (style, text.as_bytes()))
+                        .collect(),
+                )
+            }
+            Err(_) => {
+                let _ = self
+                    .lines
+                    .highlight_line(&String::from_utf8_lossy(line), self.syntaxes);
+                None
+            }
+        }
+    }
+}
+
+pub fn write_style(output: &mut impl Write, style: Style) -> io::Result<()> {
+    let colour = style.foreground;
+    write!(output, "\x1b[38;2;{};{};{}", colour.r, colour.g, colour.b)?;
+    if style.font_style.contains(FontStyle::BOLD) {
+        output.write_all(b";1")?;
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        output.write_all(b";3")?;
+    }
+    if style.font_style.contains(FontStyle::UNDERLINE) {
+        output.write_all(b";4")?;
+    }
+    output.write_all(b"m")
+}
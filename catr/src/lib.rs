@@ -1,7 +1,36 @@
-use clap::{Arg, Command};
+use clap::{Arg, ArgGroup, Command};
 use std::error::Error;
+use std::fmt;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal, Read, Write};
+use std::str::FromStr;
+use syntect::highlighting::Style;
+
+mod copy;
+mod decompress;
+mod encoding;
+mod follow;
+mod highlight;
+mod reverse;
+
+use copy::CopyError;
+use follow::{Change, Followed, Watcher};
+use highlight::{Highlighter, Highlighting};
+use regex::bytes::Regex;
+use reverse::{Reversed, Separator};
+
+// Returned by `run` when at least one input could not be read. Each failure has already been
+// reported on stderr as it happened, so all that is left is to exit with a failing status.
+#[derive(Debug)]
+pub struct InputsFailed;
+
+impl fmt::Display for InputsFailed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "one or more inputs could not be read")
+    }
+}
+
+impl Error for InputsFailed {}
 
 #[derive(Debug)]
 enum LineNumbers {
@@ -10,21 +39,96 @@ enum LineNumbers {
     OnlyNonEmpty,
 }
 
+// The lines picked out by --lines, counted from 1. Both ends are included.
+#[derive(Debug)]
+struct LineRange {
+    start: usize,
+    end: Option<usize>,
+}
+
+impl LineRange {
+    fn contains(&self, line: usize) -> bool {
+        line >= self.start && self.end.is_none_or(|end| line <= end)
+    }
+
+    fn ends_at(&self, line: usize) -> bool {
+        self.end == Some(line)
+    }
+}
+
+// START:END, where either end can be left out to run from the first line or to the last one.
+impl FromStr for LineRange {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || format!("invalid line range -- {input}");
+        let (start, end) = input.split_once(':').ok_or_else(error)?;
+        let start = match start {
+            "" => 1,
+            _ => start.parse().map_err(|_| error())?,
+        };
+        let end = match end {
+            "" => None,
+            _ => Some(end.parse().map_err(|_| error())?),
+        };
+
+        match (start, end) {
+            (0, _) => Err(error()),
+            (start, Some(end)) if end < start => Err(error()),
+            _ => Ok(LineRange { start, end }),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Config {
     show_ends: bool,
+    show_nonprinting: bool,
+    show_tabs: bool,
     line_numbers: LineNumbers,
+    number_width: usize,
+    number_separator: String,
+    number_start: usize,
+    number_per_file: bool,
     squeeze_blank: bool,
+    follow: bool,
+    decompress: bool,
+    highlighting: Option<Highlighting>,
+    lines: Option<LineRange>,
+    lines_per_file: bool,
+    reverse: Option<Separator>,
+    show_crlf: bool,
+    crlf_to_lf: bool,
+    strip_bom: bool,
+    to_utf8: bool,
+    detect_encoding: bool,
     input: Vec<String>,
 }
 
+// Where the output has got to. Lines are split at '\n' on the concatenated input, as cat does: if a
+// file does not end in a newline, its last line carries on into the first line of the next file.
+// So whether we are at the start of a line, and whether the last line was empty, outlive each file.
+struct LineState {
+    // None once the numbers have run out, which is only an error if another line is numbered.
+    line_number: Option<usize>,
+    at_line_start: bool,
+    previous_line_empty: bool,
+}
+
 impl Config {
     pub fn new() -> Result<Self, Box<dyn Error>> {
         let matches = Command::new("catr")
             .version("0.1.0")
             .author("BeniiAlCo")
             .about("rust port of (a subset of) cat")
-            //-bEns TODO: implement -A, -e, -t, -T, -u, -v
+            .arg(
+                Arg::new("show_all")
+                    .short('A')
+                    .long("show-all")
+                    .help("equivalent to -vET")
+                    .multiple_occurrences(true)
+                    .display_order(0),
+            )
             .arg(
                 Arg::new("number_nonblank")
                     .short('b')
@@ -33,13 +137,20 @@ impl Config {
                     .multiple_occurrences(true)
                     .display_order(1),
             )
+            .arg(
+                Arg::new("e")
+                    .short('e')
+                    .help("equivalent to -vE")
+                    .multiple_occurrences(true)
+                    .display_order(2),
+            )
             .arg(
                 Arg::new("show_ends")
                     .short('E')
                     .long("show-ends")
                     .help("display $ at end of each line")
                     .multiple_occurrences(true)
-                    .display_order(2),
+                    .display_order(3),
             )
             .arg(
                 Arg::new("number")
@@ -47,7 +158,7 @@ impl Config {
                     .long("number")
                     .help("number all output lines")
                     .multiple_occurrences(true)
-                    .display_order(3),
+                    .display_order(4),
             )
             .arg(
                 Arg::new("squeeze_blank")
@@ -55,7 +166,191 @@ impl Config {
                     .long("squeeze-blank")
                     .help("suppress repeated empty output lines")
                     .multiple_occurrences(true)
-                    .display_order(4),
+                    .display_order(5),
+            )
+            .arg(
+                Arg::new("t")
+                    .short('t')
+                    .help("equivalent to -vT")
+                    .multiple_occurrences(true)
+                    .display_order(6),
+            )
+            .arg(
+                Arg::new("show_tabs")
+                    .short('T')
+                    .long("show-tabs")
+                    .help("display TAB characters as ^I")
+                    .multiple_occurrences(true)
+                    .display_order(7),
+            )
+            .arg(
+                Arg::new("u")
+                    .short('u')
+                    .help("(ignored)")
+                    .multiple_occurrences(true)
+                    .display_order(8),
+            )
+            .arg(
+                Arg::new("show_nonprinting")
+                    .short('v')
+                    .long("show-nonprinting")
+                    .help("use ^ and M- notation, except for LFD and TAB")
+                    .multiple_occurrences(true)
+                    .display_order(9),
+            )
+            .arg(
+                Arg::new("decompress")
+                    .short('z')
+                    .long("decompress")
+                    .help(
+                        "decompress gzip, bzip2, xz and zstd input, passing anything else through",
+                    )
+                    .conflicts_with("follow")
+                    .display_order(10),
+            )
+            .arg(
+                Arg::new("number_width")
+                    .long("number-width")
+                    .takes_value(true)
+                    .value_name("WIDTH")
+                    .validator(valid_number_width)
+                    .requires("numbering")
+                    .help("use WIDTH columns for line numbers (default 6)")
+                    .display_order(11),
+            )
+            .arg(
+                Arg::new("number_separator")
+                    .long("number-separator")
+                    .takes_value(true)
+                    .value_name("STRING")
+                    .allow_hyphen_values(true)
+                    .requires("numbering")
+                    .help("add STRING after line numbers (default TAB)")
+                    .display_order(12),
+            )
+            .arg(
+                Arg::new("number_start")
+                    .long("number-start")
+                    .takes_value(true)
+                    .value_name("NUMBER")
+                    .validator(valid_number_start)
+                    .requires("numbering")
+                    .help("first line number (default 1)")
+                    .display_order(13),
+            )
+            .arg(
+                Arg::new("number_per_file")
+                    .long("number-per-file")
+                    .requires("numbering")
+                    .help("restart line numbers for each FILE")
+                    .display_order(14),
+            )
+            .arg(
+                Arg::new("follow")
+                    .long("follow")
+                    .help("keep reading regular files as they grow, once every FILE has been read")
+                    .display_order(15),
+            )
+            .arg(
+                Arg::new("highlight")
+                    .long("highlight")
+                    .takes_value(true)
+                    .value_name("LANG")
+                    .min_values(0)
+                    .max_values(1)
+                    .require_equals(true)
+                    .conflicts_with("follow")
+                    .help("colour the syntax of each FILE as LANG, or as its name or first line suggest")
+                    .display_order(16),
+            )
+            .arg(
+                Arg::new("color")
+                    .long("color")
+                    .takes_value(true)
+                    .value_name("WHEN")
+                    .possible_values(["always", "auto", "never"])
+                    .requires("highlight")
+                    .help("when to highlight: always, auto (only on a terminal, the default) or never")
+                    .display_order(17),
+            )
+            .arg(
+                Arg::new("lines")
+                    .long("lines")
+                    .takes_value(true)
+                    .value_name("START:END")
+                    .validator(valid_line_range)
+                    .conflicts_with("follow")
+                    .help("only output input lines START to END, numbered as they would be otherwise")
+                    .display_order(18),
+            )
+            .arg(
+                Arg::new("lines_per_file")
+                    .long("lines-per-file")
+                    .requires("lines")
+                    .help("count the lines for --lines within each FILE")
+                    .display_order(19),
+            )
+            .arg(
+                Arg::new("reverse")
+                    .long("reverse")
+                    .conflicts_with_all(&["follow", "lines"])
+                    .help("write the lines of each FILE last first, like tac")
+                    .display_order(20),
+            )
+            .arg(
+                Arg::new("separator")
+                    .long("separator")
+                    .takes_value(true)
+                    .value_name("STRING")
+                    .allow_hyphen_values(true)
+                    .validator(valid_separator)
+                    .requires("reverse")
+                    .help("use STRING rather than newline to separate lines for --reverse")
+                    .display_order(21),
+            )
+            .arg(
+                Arg::new("regex")
+                    .long("regex")
+                    .requires("separator")
+                    .help("take the --separator STRING as a regular expression")
+                    .display_order(22),
+            )
+            .arg(
+                Arg::new("show_crlf")
+                    .long("show-crlf")
+                    .help("display ^M$ at the end of lines ending in CRLF, and $ at the end of the rest")
+                    .display_order(23),
+            )
+            .arg(
+                Arg::new("crlf_to_lf")
+                    .long("crlf-to-lf")
+                    .help("end lines with LF where they end with CRLF")
+                    .display_order(24),
+            )
+            .arg(
+                Arg::new("strip_bom")
+                    .long("strip-bom")
+                    .help("leave out the UTF-8 byte order mark at the start of each FILE")
+                    .display_order(25),
+            )
+            .arg(
+                Arg::new("to_utf8")
+                    .long("to-utf8")
+                    .conflicts_with("follow")
+                    .help("convert UTF-16 input to UTF-8")
+                    .display_order(26),
+            )
+            .arg(
+                Arg::new("detect_encoding")
+                    .long("detect-encoding")
+                    .conflicts_with_all(&["follow", "reverse", "lines", "highlight"])
+                    .help("write the encoding of each FILE instead of its contents")
+                    .display_order(27),
+            )
+            .group(
+                ArgGroup::new("numbering")
+                    .args(&["number", "number_nonblank"])
+                    .multiple(true),
             )
             .arg(
                 Arg::new("input")
@@ -67,8 +362,38 @@ impl Config {
             )
             .get_matches();
 
+        let show_all = matches.is_present("show_all");
+
+        // The language is checked even when nothing is going to be highlighted, so that a typo
+        // does not go unnoticed until the output reaches a terminal.
+        let highlighting = match matches.is_present("highlight") {
+            true => Some(Highlighting::new(matches.value_of("highlight"))?),
+            false => None,
+        };
+        let separator = matches.value_of("separator").unwrap_or("\n");
+        let reverse = match (matches.is_present("reverse"), matches.is_present("regex")) {
+            (false, _) => None,
+            (true, false) => Some(Separator::Literal(separator.as_bytes().to_vec())),
+            (true, true) => Some(Separator::Regex(
+                Regex::new(separator).map_err(|e| format!("invalid regex '{separator}': {e}"))?,
+            )),
+        };
+        let colour = match matches.value_of("color") {
+            Some("always") => true,
+            Some("never") => false,
+            _ => io::stdout().is_terminal(),
+        };
+
         Ok(Config {
-            show_ends: matches.is_present("show_ends"),
+            show_ends: show_all
+                || matches.is_present("e")
+                || matches.is_present("show_ends")
+                || matches.is_present("show_crlf"),
+            show_nonprinting: show_all
+                || matches.is_present("e")
+                || matches.is_present("t")
+                || matches.is_present("show_nonprinting"),
+            show_tabs: show_all || matches.is_present("t") || matches.is_present("show_tabs"),
             line_numbers: {
                 match matches.is_present("number_nonblank") {
                     true => LineNumbers::OnlyNonEmpty,
@@ -76,7 +401,25 @@ impl Config {
                     _ => LineNumbers::Omit,
                 }
             },
+            number_width: matches.value_of_t("number_width").unwrap_or(6),
+            number_separator: matches
+                .value_of("number_separator")
+                .unwrap_or("\t")
+                .to_string(),
+            number_start: matches.value_of_t("number_start").unwrap_or(1),
+            number_per_file: matches.is_present("number_per_file"),
             squeeze_blank: matches.is_present("squeeze_blank"),
+            follow: matches.is_present("follow"),
+            decompress: matches.is_present("decompress"),
+            highlighting: highlighting.filter(|_| colour),
+            lines: matches.value_of_t("lines").ok(),
+            lines_per_file: matches.is_present("lines_per_file"),
+            reverse,
+            show_crlf: matches.is_present("show_crlf"),
+            crlf_to_lf: matches.is_present("crlf_to_lf"),
+            strip_bom: matches.is_present("strip_bom"),
+            to_utf8: matches.is_present("to_utf8"),
+            detect_encoding: matches.is_present("detect_encoding"),
 
             // TODO: does matches have to be a String? Surely I can leave it as an &str somehow?
             // Would this mean foregoing the Vec here and accessing the input files some other way
@@ -94,64 +437,436 @@ impl Config {
     }
 
     pub fn run(self) -> Result<(), Box<dyn Error>> {
-        if self.squeeze_blank {}
-        match self.line_numbers {
-            _ => {}
+        if self.detect_encoding {
+            return self.detect_encodings();
+        }
+        if self.copies_verbatim() && !self.follow && !self.decompress && self.reverse.is_none() {
+            return self.copy_input();
         }
 
-        let mut line_number = 0;
+        let mut output = BufWriter::new(io::stdout().lock());
+        let mut state = LineState {
+            line_number: Some(self.number_start),
+            at_line_start: true,
+            previous_line_empty: false,
+        };
+        let mut line = Vec::new();
+        let mut failed = false;
+        let mut followed = Vec::new();
+        // Which line of the input, or of the current file with --lines-per-file, is being read.
+        let mut input_line = 0;
+        let mut done = false;
 
         // TODO: Can this loop be written as an iterator that unpacks each file, whilst using
         // correct error messages, flattens them as to maintain the correct line number, and then
         // prints out each line without using additional string allocations?
-        for filename in self.input {
-            match Config::open_file(&filename) {
-                Err(e) => eprintln!("Failed to open {filename}: {e}"),
-                Ok(o) => {
-                    let mut previous_line_empty = false;
-                    for line in o.lines() {
-                        let line_contents = line?;
-
-                        if self.squeeze_blank {
-                            if previous_line_empty && line_contents.is_empty() {
-                                continue;
-                            }
+        for filename in &self.input {
+            if done {
+                break;
+            }
+            if self.number_per_file {
+                state.line_number = Some(self.number_start);
+            }
+            if self.lines_per_file {
+                input_line = 0;
+            }
 
-                            previous_line_empty = line_contents.is_empty();
-                        }
+            match self.open_input(filename, &mut followed) {
+                Err(e) => {
+                    output.flush()?;
+                    report(filename, e);
+                    failed = true;
+                }
+                Ok(mut o) => {
+                    let mut highlighter = self.highlighter(filename, &mut o);
+                    loop {
+                        line.clear();
+                        match o.read_until(b'\n', &mut line) {
+                            Ok(0) => break,
+                            Ok(_) => {
+                                // Lines carried on from the previous file count as one line,
+                                // unless the counting starts again with each file.
+                                if state.at_line_start || input_line == 0 {
+                                    input_line += 1;
+                                }
 
-                        // TODO: This uses an additional string allocation to use the format macro
-                        // -- I need to check how the println macro works to see if this truly
-                        // matters, or if it merely pre-emts an allocation anyway.
-                        // If it need not use an allocation, I should see if I can generate the
-                        // line number in another way; If it will allocate to print, I should
-                        // define the expected length of the string here to save on having to
-                        // expand it when concatinating the line parts and printing.
-                        let line_start = match self.line_numbers {
-                            LineNumbers::Omit => format!(""),
-                            LineNumbers::Include => {
-                                line_number += 1;
-                                format!("{:>6}\t", line_number)
-                            }
-                            LineNumbers::OnlyNonEmpty => {
-                                if !line_contents.is_empty() {
-                                    line_number += 1;
-                                    format!("{:>6}\t", line_number)
-                                } else {
-                                    format!("")
+                                // Lines outside the range still go through everything but the
+                                // output, so that numbering and squeezing carry on as they would
+                                // have.
+                                match &self.lines {
+                                    Some(lines) if !lines.contains(input_line) => self.write_line(
+                                        &mut io::sink(),
+                                        &mut state,
+                                        highlighter.as_mut(),
+                                        &line,
+                                    )?,
+                                    _ => self.write_line(
+                                        &mut output,
+                                        &mut state,
+                                        highlighter.as_mut(),
+                                        &line,
+                                    )?,
                                 }
+
+                                // Nothing more can be picked out once the last line of the range
+                                // is complete, so there is no need to read any further.
+                                if state.at_line_start
+                                    && self
+                                        .lines
+                                        .as_ref()
+                                        .is_some_and(|lines| lines.ends_at(input_line))
+                                {
+                                    done = !self.lines_per_file;
+                                    break;
+                                }
+                            }
+                            Err(e) => {
+                                output.flush()?;
+                                report(filename, e);
+                                failed = true;
+                                followed.retain(|file: &Followed| file.name() != filename);
+                                break;
                             }
-                        };
+                        }
+                    }
+                }
+            }
+        }
+
+        output.flush()?;
+        if !followed.is_empty() {
+            self.follow(followed, &mut output, &mut state)?;
+            failed = true;
+        }
+        finish(failed)
+    }
+
+    // Only returns once every followed file has failed.
+    fn follow(
+        &self,
+        mut followed: Vec<Followed>,
+        output: &mut impl Write,
+        state: &mut LineState,
+    ) -> io::Result<()> {
+        let mut watcher = Watcher::new(&followed);
+        let mut buffer = vec![0; 64 * 1024];
+
+        while !followed.is_empty() {
+            watcher.wait();
+
+            let mut index = 0;
+            while index < followed.len() {
+                let file = &mut followed[index];
+                let result = loop {
+                    // Everything already in the file has to be written out before checking it, as
+                    // a replaced file is closed by the check.
+                    match file.read(&mut buffer) {
+                        Ok(0) => {}
+                        Ok(bytes) => {
+                            for line in buffer[..bytes].split_inclusive(|&byte| byte == b'\n') {
+                                self.write_line(output, state, None, line)?;
+                            }
+                            continue;
+                        }
+                        Err(e) => break Err(e),
+                    }
+
+                    match file.check() {
+                        Ok(None) => break Ok(()),
+                        Ok(Some(Change::Truncated)) => {
+                            output.flush()?;
+                            report(file.name(), "file truncated");
+                        }
+                        Ok(Some(Change::Replaced)) => {
+                            output.flush()?;
+                            report(file.name(), "file has been replaced; following new file");
+                        }
+                        Err(e) => break Err(e),
+                    }
+                };
+
+                match result {
+                    Ok(()) => index += 1,
+                    Err(e) => {
+                        output.flush()?;
+                        report(followed[index].name(), e);
+                        followed.remove(index);
+                    }
+                }
+            }
+
+            output.flush()?;
+        }
+
+        Ok(())
+    }
+
+    // Writes a single line, or the part of one that has been read so far, with `line` including its
+    // '\n' if it has one.
+    fn write_line(
+        &self,
+        output: &mut impl Write,
+        state: &mut LineState,
+        highlighter: Option<&mut Highlighter>,
+        line: &[u8],
+    ) -> io::Result<()> {
+        let (line_contents, terminated) = match line.strip_suffix(b"\n") {
+            Some(line_contents) => (line_contents, true),
+            None => (line, false),
+        };
+        // A line that is nothing but a dropped CR counts as empty for -b and -s.
+        let line_contents = match line_contents.strip_suffix(b"\r") {
+            Some(line_contents) if terminated && self.crlf_to_lf => line_contents,
+            _ => line_contents,
+        };
+        let starts_line = state.at_line_start;
+        state.at_line_start = terminated;
+
+        if starts_line {
+            if self.squeeze_blank {
+                if state.previous_line_empty && line_contents.is_empty() {
+                    return Ok(());
+                }
+
+                state.previous_line_empty = line_contents.is_empty();
+            }
+
+            let numbered = match self.line_numbers {
+                LineNumbers::Omit => false,
+                LineNumbers::Include => true,
+                LineNumbers::OnlyNonEmpty => !line_contents.is_empty(),
+            };
+            if numbered {
+                let line_number = state.line_number.ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "line number overflow")
+                })?;
+                write!(
+                    output,
+                    "{:>width$}{}",
+                    line_number,
+                    self.number_separator,
+                    width = self.number_width
+                )?;
+                state.line_number = line_number.checked_add(1);
+            }
+        }
+
+        // A CR that is shown is written out as part of the line ending, after any colour is reset.
+        let (line_contents, crlf) = match line_contents.strip_suffix(b"\r") {
+            Some(line_contents) if terminated && self.show_crlf => (line_contents, true),
+            _ => (line_contents, false),
+        };
+        match highlighter.and_then(|highlighter| highlighter.highlight(line)) {
+            Some(regions) => self.write_highlighted(output, &regions, line_contents.len())?,
+            None => self.write_contents(output, line_contents)?,
+        }
+
+        if terminated {
+            if crlf {
+                output.write_all(b"^M")?;
+            }
+            if self.show_ends {
+                output.write_all(b"$")?;
+            }
+            output.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    // With --decompress, every input goes through the decompressor, which passes anything it does
+    // not recognise through untouched.
+    // With --follow, regular files are kept open once they have been read, ready to be followed.
+    // The copy of the file handle shares its position with the one being read from, so it is left
+    // exactly where reading stopped.
+    fn open_input(
+        &self,
+        filename: &str,
+        followed: &mut Vec<Followed>,
+    ) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+        if let Some(separator) = &self.reverse {
+            return Ok(self.open_reversed(filename, separator)?);
+        }
+
+        let input = self.open_forwards(filename, followed)?;
+        Ok(self.normalise(input)?)
+    }
+
+    fn open_forwards(
+        &self,
+        filename: &str,
+        followed: &mut Vec<Followed>,
+    ) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+        if self.decompress {
+            return match filename {
+                "-" => Ok(decompress::open(io::stdin())?),
+                _ => Ok(decompress::open(File::open(filename)?)?),
+            };
+        }
+        if !self.follow || filename == "-" {
+            return Config::open_file(filename);
+        }
+
+        let file = File::open(filename)?;
+        if let Some(file) = Followed::new(filename, file.try_clone()?)? {
+            followed.push(file);
+        }
+        Ok(Box::new(BufReader::new(file)))
+    }
+
+    // Regular files are read backwards where they are. Anything else can only be read forwards, so
+    // has to be copied into a temporary file first.
+    fn open_reversed(&self, filename: &str, separator: &Separator) -> io::Result<Box<dyn BufRead>> {
+        let input: Box<dyn Read> = match filename {
+            "-" => Box::new(io::stdin()),
+            _ => {
+                let file = File::open(filename)?;
+                if !self.decompress && !self.normalises() && file.metadata()?.is_file() {
+                    return Ok(Box::new(Reversed::new(file, separator.clone())?));
+                }
+                Box::new(file)
+            }
+        };
+
+        let input = match self.decompress {
+            true => decompress::open(input)?,
+            false => Box::new(BufReader::new(input)),
+        };
+        Ok(Box::new(reverse::spooled(
+            self.normalise(input)?,
+            separator.clone(),
+        )?))
+    }
+
+    fn normalises(&self) -> bool {
+        self.strip_bom || self.to_utf8
+    }
+
+    fn normalise(&self, input: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+        match self.normalises() {
+            true => encoding::normalise(input, self.strip_bom, self.to_utf8),
+            false => Ok(input),
+        }
+    }
+
+    // The encoding is worked out from what would otherwise be written out, so with --decompress it
+    // is the encoding of the decompressed data.
+    fn detect_encodings(&self) -> Result<(), Box<dyn Error>> {
+        let mut output = io::stdout().lock();
+        let mut failed = false;
 
-                        // TODO: Add in tests for line_end.
-                        let line_end = if self.show_ends { "$" } else { "" };
+        for filename in &self.input {
+            let detected = self
+                .open_forwards(filename, &mut Vec::new())
+                .and_then(|mut input| Ok(encoding::detect(&mut input)?));
+            match detected {
+                Ok(encoding) => writeln!(output, "{filename}: {encoding}")?,
+                Err(e) => {
+                    output.flush()?;
+                    report(filename, e);
+                    failed = true;
+                }
+            }
+        }
+
+        finish(failed)
+    }
+
+    // With none of the formatting options set, the output is the input byte for byte, so there is no
+    // need to look for line breaks at all.
+    fn copies_verbatim(&self) -> bool {
+        !self.show_ends
+            && !self.show_nonprinting
+            && !self.show_tabs
+            && !self.squeeze_blank
+            && matches!(self.line_numbers, LineNumbers::Omit)
+            && self.highlighting.is_none()
+            && self.lines.is_none()
+            && !self.crlf_to_lf
+            && !self.normalises()
+    }
 
-                        println!("{line_start}{line_contents}{line_end}");
+    fn copy_input(&self) -> Result<(), Box<dyn Error>> {
+        let mut output = io::stdout().lock();
+        let mut failed = false;
+
+        for filename in &self.input {
+            let copied = match filename.as_str() {
+                "-" => copy::copy(&mut io::stdin().lock(), &mut output),
+                _ => match File::open(filename) {
+                    Err(e) => {
+                        report(filename, e);
+                        failed = true;
+                        continue;
                     }
+                    Ok(mut file) => copy::copy(&mut file, &mut output),
+                },
+            };
+
+            match copied {
+                Ok(_) => {}
+                Err(CopyError::Read(e)) => {
+                    report(filename, e);
+                    failed = true;
                 }
+                Err(CopyError::Write(e)) => return Err(Box::new(e)),
             }
         }
 
+        finish(failed)
+    }
+
+    // Only the first `length` bytes are written, leaving out the newline that the last region ends
+    // with, so that the colour is reset before any `$` and the newline itself.
+    fn write_highlighted(
+        &self,
+        output: &mut impl Write,
+        regions: &[(Style, &[u8])],
+        mut length: usize,
+    ) -> io::Result<()> {
+        if length == 0 {
+            return Ok(());
+        }
+
+        let mut current = None;
+        for &(style, text) in regions {
+            let text = &text[..text.len().min(length)];
+            if text.is_empty() {
+                break;
+            }
+            if current != Some(style) {
+                highlight::write_style(output, style)?;
+                current = Some(style);
+            }
+            self.write_contents(output, text)?;
+            length -= text.len();
+        }
+        output.write_all(highlight::RESET)
+    }
+
+    // Works out the language from the start of the input when it is not given, without taking
+    // anything out of the input.
+    fn highlighter(&self, filename: &str, input: &mut dyn BufRead) -> Option<Highlighter<'_>> {
+        let highlighting = self.highlighting.as_ref()?;
+        let start = input.fill_buf().unwrap_or_default();
+        let first_line = start
+            .split(|&byte| byte == b'\n')
+            .next()
+            .unwrap_or_default();
+        Some(highlighting.for_input(filename, &String::from_utf8_lossy(first_line)))
+    }
+
+    fn write_contents(&self, output: &mut impl Write, contents: &[u8]) -> io::Result<()> {
+        if !self.show_nonprinting && !self.show_tabs {
+            return output.write_all(contents);
+        }
+
+        for &byte in contents {
+            match byte {
+                b'\t' if self.show_tabs => output.write_all(b"^I")?,
+                _ if self.show_nonprinting => write_nonprinting(output, byte)?,
+                _ => output.write_all(&[byte])?,
+            }
+        }
         Ok(())
     }
 
@@ -165,3 +880,62 @@ impl Config {
         }
     }
 }
+
+fn report(filename: &str, e: impl fmt::Display) {
+    eprintln!("catr: {filename}: {e}");
+}
+
+fn finish(failed: bool) -> Result<(), Box<dyn Error>> {
+    if failed {
+        Err(Box::new(InputsFailed))
+    } else {
+        Ok(())
+    }
+}
+
+fn valid_number_width(input: &str) -> Result<(), String> {
+    match input.parse::<usize>() {
+        Ok(width) if width > 0 => Ok(()),
+        _ => Err(format!("invalid line number field width -- {input}")),
+    }
+}
+
+fn valid_number_start(input: &str) -> Result<(), String> {
+    match input.parse::<usize>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("invalid starting line number -- {input}")),
+    }
+}
+
+fn valid_separator(input: &str) -> Result<(), String> {
+    match input.is_empty() {
+        true => Err("separator cannot be empty".to_string()),
+        false => Ok(()),
+    }
+}
+
+fn valid_line_range(input: &str) -> Result<(), String> {
+    input.parse::<LineRange>().map(|_| ())
+}
+
+// Writes `byte` in the caret notation used by cat -v: control characters become ^X, DEL becomes ^?,
+// and bytes with the high bit set are written as M- followed by the notation for the low seven bits.
+// TAB is left alone, as -T is the flag that decides how it is shown.
+fn write_nonprinting(output: &mut impl Write, byte: u8) -> io::Result<()> {
+    if byte == b'\t' {
+        return output.write_all(&[byte]);
+    }
+
+    let byte = if byte >= 128 {
+        output.write_all(b"M-")?;
+        byte - 128
+    } else {
+        byte
+    };
+
+    match byte {
+        0..=31 => output.write_all(&[b'^', byte + 64]),
+        127 => output.write_all(b"^?"),
+        _ => output.write_all(&[byte]),
+    }
+}
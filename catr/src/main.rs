@@ -1,6 +1,8 @@
 fn main() {
     if let Err(e) = catr::Config::new().and_then(catr::Config::run) {
-        eprintln!("{}", e);
+        if !e.is::<catr::InputsFailed>() {
+            eprintln!("catr: {}", e);
+        }
         std::process::exit(1);
     }
 }
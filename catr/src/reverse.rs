@@ -0,0 +1,132 @@
+// Reading input last record first, for --reverse.
+//
+// A record is everything up to and including a separator, so records are written out in reverse
+// order with their separators still after them, as tac does. Regular files are read backwards in
+// blocks from the end, so only the records that end in the current block (plus whatever comes
+// before the first separator in it) are ever held in memory. Anything that can only be read
+// forwards is copied into a temporary file first.
+
+use memchr::memmem;
+use regex::bytes::Regex;
+use std::fs::File;
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+const BLOCK_SIZE: u64 = 64 * 1024;
+
+#[derive(Clone, Debug)]
+pub enum Separator {
+    Literal(Vec<u8>),
+    Regex(Regex),
+}
+
+impl Separator {
+    // The start and end of each separator in `haystack`, in order. A regex that matches nothing at
+    // all is never taken as a separator.
+    fn find_all(&self, haystack: &[u8]) -> Vec<(usize, usize)> {
+        match self {
+            Separator::Literal(separator) => memmem::find_iter(haystack, separator)
+                .map(|start| (start, start + separator.len()))
+                .collect(),
+            Separator::Regex(regex) => regex
+                .find_iter(haystack)
+                .filter(|found| !found.as_bytes().is_empty())
+                .map(|found| (found.start(), found.end()))
+                .collect(),
+        }
+    }
+}
+
+pub struct Reversed<R> {
+    input: R,
+    // Where in the input the bytes in `pending` start.
+    position: u64,
+    // The part of the input between `position` and the records that have already been output,
+    // which starts before the first separator that has been found in it.
+    pending: Vec<u8>,
+    separator: Separator,
+    // Records waiting to be read, already in reverse order, and how much of them has been.
+    output: Vec<u8>,
+    consumed: usize,
+}
+
+impl<R: Read + Seek> Reversed<R> {
+    pub fn new(mut input: R, separator: Separator) -> io::Result<Self> {
+        let position = input.seek(SeekFrom::End(0))?;
+        Ok(Reversed {
+            input,
+            position,
+            pending: Vec::new(),
+            separator,
+            output: Vec::new(),
+            consumed: 0,
+        })
+    }
+
+    // Reads blocks backwards until at least one more record is complete. `output` is only left
+    // empty once the whole input has been output.
+    fn fill(&mut self) -> io::Result<()> {
+        self.output.clear();
+        self.consumed = 0;
+
+        while self.output.is_empty() {
+            // Whatever is left is the first record, which has no separator before it.
+            if self.position == 0 {
+                self.output.append(&mut self.pending);
+                return Ok(());
+            }
+
+            let size = BLOCK_SIZE.min(self.position);
+            self.position -= size;
+            self.input.seek(SeekFrom::Start(self.position))?;
+            let mut block = vec![0; size as usize];
+            self.input.read_exact(&mut block)?;
+            block.extend_from_slice(&self.pending);
+            self.pending = block;
+
+            let mut separators = self.separator.find_all(&self.pending);
+            // A regex match at the very start of the block might only be the end of a longer
+            // match, which will be found once the block before it has been read.
+            if self.position > 0 && separators.first().map(|&(start, _)| start) == Some(0) {
+                separators.remove(0);
+            }
+
+            let mut end = self.pending.len();
+            for &(_, separator_end) in separators.iter().rev() {
+                self.output
+                    .extend_from_slice(&self.pending[separator_end..end]);
+                end = separator_end;
+            }
+            self.pending.truncate(end);
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Read for Reversed<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let bytes = self.fill_buf()?.read(buffer)?;
+        self.consume(bytes);
+        Ok(bytes)
+    }
+}
+
+impl<R: Read + Seek> BufRead for Reversed<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.consumed == self.output.len() {
+            self.fill()?;
+        }
+        Ok(&self.output[self.consumed..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.consumed = (self.consumed + amount).min(self.output.len());
+    }
+}
+
+// For input that can only be read forwards, such as stdin or decompressed data.
+pub fn spooled(mut input: impl Read, separator: Separator) -> io::Result<Reversed<File>> {
+    let mut file = tempfile::tempfile()?;
+    io::copy(&mut input, &mut file)?;
+    Reversed::new(file, separator)
+}
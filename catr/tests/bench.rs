@@ -0,0 +1,67 @@
+use assert_cmd::cargo::cargo_bin;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tempfile::NamedTempFile;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "catr";
+const INPUT_SIZE: usize = 256 * 1024 * 1024;
+
+// --------------------------------------------------
+fn gen_input() -> Result<NamedTempFile, Box<dyn Error>> {
+    let input = NamedTempFile::new()?;
+    let mut writer = BufWriter::new(input.reopen()?);
+    let mut written = 0;
+    let mut line_number = 0;
+
+    while written < INPUT_SIZE {
+        line_number += 1;
+        let line = format!("line {line_number} of the input used to time catr\n");
+        writer.write_all(line.as_bytes())?;
+        written += line.len();
+    }
+
+    writer.flush()?;
+    Ok(input)
+}
+
+// --------------------------------------------------
+fn time_catr(args: &[&str], output: &NamedTempFile) -> Result<Duration, Box<dyn Error>> {
+    let start = Instant::now();
+    let status = Command::new(cargo_bin(PRG))
+        .args(args)
+        .stdout(File::create(output.path())?)
+        .status()?;
+    let elapsed = start.elapsed();
+
+    assert!(status.success());
+    Ok(elapsed)
+}
+
+// --------------------------------------------------
+// Compares the fast path against the line-based loop, which `-s` forces without changing the
+// output, as the input has no blank lines to squeeze.
+// Run with `cargo test --release --test bench -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn fast_path_against_line_loop() -> TestResult {
+    let input = gen_input()?;
+    let input_path = input.path().to_str().unwrap();
+    let fast_output = NamedTempFile::new()?;
+    let line_output = NamedTempFile::new()?;
+
+    let fast = time_catr(&[input_path], &fast_output)?;
+    let line = time_catr(&["-s", input_path], &line_output)?;
+
+    println!("{} MiB", INPUT_SIZE / (1024 * 1024));
+    println!("fast path: {fast:?}");
+    println!("line loop: {line:?}");
+
+    assert_eq!(fs::read(fast_output.path())?, fs::read(input.path())?);
+    assert_eq!(fs::read(line_output.path())?, fs::read(input.path())?);
+    Ok(())
+}
@@ -12,6 +12,18 @@ const FOX: &str = "tests/inputs/fox.txt";
 const SPIDERS: &str = "tests/inputs/spiders.txt";
 const BUSTLE: &str = "tests/inputs/the-bustle.txt";
 const ONE_TWO_THREE_FOUR: &str = "tests/inputs/one-two-three-four.txt";
+const NONPRINTING: &str = "tests/inputs/nonprinting.txt";
+const TABS: &str = "tests/inputs/tabs.txt";
+const NO_NEWLINE: &str = "tests/inputs/no-newline.txt";
+const BUSTLE_GZ: &str = "tests/inputs/the-bustle.txt.gz";
+const ALL_GZ: &str = "tests/inputs/all.txt.gz";
+const CORRUPT_GZ: &str = "tests/inputs/corrupt.gz";
+const BZH: &str = "tests/inputs/bzh.txt";
+const HELLO_RS: &str = "tests/inputs/hello.rs";
+const CRLF: &str = "tests/inputs/crlf.txt";
+const UTF16LE: &str = "tests/inputs/utf16le.txt";
+const UTF16BE: &str = "tests/inputs/utf16be.txt";
+const LATIN1: &str = "tests/inputs/latin1.txt";
 
 // --------------------------------------------------
 #[test]
@@ -44,18 +56,39 @@ fn gen_bad_file() -> String {
 #[test]
 fn skips_bad_file() -> TestResult {
     let bad = gen_bad_file();
-    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    let expected = format!("^catr: {}: .* [(]os error 2[)]\n$", bad);
     Command::cargo_bin(PRG)?
         .arg(&bad)
         .assert()
-        .success()
+        .failure()
         .stderr(predicate::str::is_match(expected)?);
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn continues_past_bad_files() -> TestResult {
+    let bad = gen_bad_file();
+    let expected =
+        format!("^catr: {bad}: .* [(]os error 2[)]\ncatr: tests/inputs: .* [(]os error 21[)]\n$");
+    for (flag, expected_file) in [
+        ("-u", "tests/expected/all.out"),
+        ("-n", "tests/expected/all.n.out"),
+    ] {
+        let output = fs::read_to_string(expected_file)?;
+        Command::cargo_bin(PRG)?
+            .args([flag, FOX, &bad, SPIDERS, "tests/inputs", BUSTLE])
+            .assert()
+            .failure()
+            .stdout(output)
+            .stderr(predicate::str::is_match(&expected)?);
+    }
+    Ok(())
+}
+
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> TestResult {
-    let expected = fs::read_to_string(expected_file)?;
+    let expected = fs::read(expected_file)?;
     Command::cargo_bin(PRG)?
         .args(args)
         .assert()
@@ -66,8 +99,8 @@ fn run(args: &[&str], expected_file: &str) -> TestResult {
 
 // --------------------------------------------------
 fn run_stdin(input_file: &str, args: &[&str], expected_file: &str) -> TestResult {
-    let input = fs::read_to_string(input_file)?;
-    let expected = fs::read_to_string(expected_file)?;
+    let input = fs::read(input_file)?;
+    let expected = fs::read(expected_file)?;
     Command::cargo_bin(PRG)?
         .args(args)
         .write_stdin(input)
@@ -231,3 +264,829 @@ fn one_two_three_four_b_s() -> TestResult {
         "tests/expected/one-two-three-four.b.s.txt",
     )
 }
+
+// ---
+#[test]
+fn nonprinting_v() -> TestResult {
+    run(&[NONPRINTING, "-v"], "tests/expected/nonprinting.txt.v.out")
+}
+
+// ---
+#[test]
+fn nonprinting_show_nonprinting() -> TestResult {
+    run(
+        &[NONPRINTING, "--show-nonprinting"],
+        "tests/expected/nonprinting.txt.v.out",
+    )
+}
+
+// ---
+#[test]
+fn nonprinting_a() -> TestResult {
+    run(&[NONPRINTING, "-A"], "tests/expected/nonprinting.txt.A.out")
+}
+
+// ---
+#[test]
+fn nonprinting_show_all() -> TestResult {
+    run(
+        &[NONPRINTING, "--show-all"],
+        "tests/expected/nonprinting.txt.A.out",
+    )
+}
+
+// ---
+#[test]
+fn nonprinting_e() -> TestResult {
+    run(&[NONPRINTING, "-e"], "tests/expected/nonprinting.txt.e.out")
+}
+
+// ---
+#[test]
+fn nonprinting_t() -> TestResult {
+    run(&[NONPRINTING, "-t"], "tests/expected/nonprinting.txt.t.out")
+}
+
+// ---
+#[test]
+fn nonprinting_a_n() -> TestResult {
+    run(
+        &[NONPRINTING, "-A", "-n"],
+        "tests/expected/nonprinting.txt.A.n.out",
+    )
+}
+
+// ---
+#[test]
+fn nonprinting_t_b_s() -> TestResult {
+    run(
+        &[NONPRINTING, "-t", "-b", "-s"],
+        "tests/expected/nonprinting.txt.t.b.s.out",
+    )
+}
+
+// ---
+#[test]
+fn tabs_u_ignored() -> TestResult {
+    run(&[TABS, "-u"], TABS)
+}
+
+// ---
+#[test]
+fn tabs_t() -> TestResult {
+    run(&[TABS, "-T"], "tests/expected/tabs.txt.T.out")
+}
+
+// ---
+#[test]
+fn tabs_show_tabs_stdin() -> TestResult {
+    run_stdin(TABS, &["--show-tabs"], "tests/expected/tabs.txt.T.out")
+}
+
+// ---
+#[test]
+fn tabs_v() -> TestResult {
+    run(&[TABS, "-v"], "tests/expected/tabs.txt.v.out")
+}
+
+// ---
+#[test]
+fn tabs_a() -> TestResult {
+    run(&[TABS, "-A"], "tests/expected/tabs.txt.A.out")
+}
+
+// ---
+#[test]
+fn nonprinting() -> TestResult {
+    run(&[NONPRINTING], NONPRINTING)
+}
+
+// ---
+#[test]
+fn nonprinting_stdin() -> TestResult {
+    run_stdin(NONPRINTING, &[], NONPRINTING)
+}
+
+// ---
+#[test]
+fn no_newline() -> TestResult {
+    run(&[NO_NEWLINE], NO_NEWLINE)
+}
+
+// ---
+#[test]
+fn no_newline_n() -> TestResult {
+    run(&[NO_NEWLINE, "-n"], "tests/expected/no-newline.txt.n.out")
+}
+
+// ---
+#[test]
+fn no_newline_e() -> TestResult {
+    run(&[NO_NEWLINE, "-E"], "tests/expected/no-newline.txt.E.out")
+}
+
+// ---
+#[test]
+fn no_newline_stdin_e() -> TestResult {
+    run_stdin(NO_NEWLINE, &["-E"], "tests/expected/no-newline.txt.E.out")
+}
+
+// ---
+#[test]
+fn no_newline_continues_into_next_file() -> TestResult {
+    run(
+        &[NO_NEWLINE, FOX, NO_NEWLINE, ONE_TWO_THREE_FOUR, "-b", "-E"],
+        "tests/expected/no-newline.all.b.E.out",
+    )
+}
+
+// ---
+#[test]
+fn one_two_three_four_twice_s() -> TestResult {
+    run(
+        &[ONE_TWO_THREE_FOUR, ONE_TWO_THREE_FOUR, "-s"],
+        "tests/expected/one-two-three-four.twice.s.out",
+    )
+}
+
+// ---
+#[test]
+fn fast_path_to_file() -> TestResult {
+    // Writing to a regular file rather than a pipe lets the copy go through copy_file_range.
+    let output = tempfile::NamedTempFile::new()?;
+    let status = std::process::Command::new(assert_cmd::cargo::cargo_bin(PRG))
+        .args([FOX, SPIDERS, BUSTLE])
+        .stdout(output.reopen()?)
+        .status()?;
+
+    assert!(status.success());
+    assert_eq!(
+        fs::read(output.path())?,
+        fs::read("tests/expected/all.out")?
+    );
+    Ok(())
+}
+
+// ---
+#[test]
+fn fast_path_stdin_between_files() -> TestResult {
+    run_stdin(SPIDERS, &[FOX, "-", BUSTLE], "tests/expected/all.out")
+}
+
+// ---
+#[test]
+fn dies_number_width_without_numbering() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--number-width", "3", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("<--number|--number-nonblank>"));
+    Ok(())
+}
+
+// ---
+#[test]
+fn dies_bad_number_width() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "--number-width", "0", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid line number field width -- 0",
+        ));
+    Ok(())
+}
+
+// ---
+#[test]
+fn dies_bad_number_start() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "--number-start", "one", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid starting line number -- one",
+        ));
+    Ok(())
+}
+
+// ---
+#[test]
+fn dies_number_overflow() -> TestResult {
+    // As with nl, the last number can be used, but nothing after it.
+    Command::cargo_bin(PRG)?
+        .args(["-n", "--number-start", "18446744073709551615", FOX, FOX])
+        .assert()
+        .failure()
+        .stdout("18446744073709551615\tThe quick brown fox jumps over the lazy dog.\n")
+        .stderr("catr: line number overflow\n");
+    Ok(())
+}
+
+// ---
+#[test]
+fn all_n_width_separator_start() -> TestResult {
+    run(
+        &[
+            FOX,
+            SPIDERS,
+            BUSTLE,
+            "-n",
+            "--number-width",
+            "3",
+            "--number-separator",
+            ": ",
+            "--number-start",
+            "0",
+        ],
+        "tests/expected/all.n.w3.s.v0.out",
+    )
+}
+
+// ---
+#[test]
+fn all_n_per_file() -> TestResult {
+    run(
+        &[
+            FOX,
+            SPIDERS,
+            BUSTLE,
+            "-n",
+            "--number-per-file",
+            "--number-width",
+            "2",
+            "--number-separator",
+            " ",
+        ],
+        "tests/expected/all.n.per-file.w2.out",
+    )
+}
+
+// ---
+#[test]
+fn all_b_per_file() -> TestResult {
+    run(
+        &[
+            FOX,
+            SPIDERS,
+            BUSTLE,
+            "-b",
+            "--number-per-file",
+            "--number-width",
+            "1",
+            "--number-separator",
+            "|",
+            "--number-start",
+            "10",
+        ],
+        "tests/expected/all.b.per-file.w1.v10.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn decompress_each_format() -> TestResult {
+    for extension in ["gz", "bz2", "xz", "zst"] {
+        run(
+            &["-zn", &format!("tests/inputs/the-bustle.txt.{extension}")],
+            "tests/expected/the-bustle.txt.n.out",
+        )?;
+    }
+    Ok(())
+}
+
+// ---
+#[test]
+fn decompress_stdin() -> TestResult {
+    run_stdin(
+        BUSTLE_GZ,
+        &["--decompress", "-n"],
+        "tests/expected/the-bustle.txt.n.out",
+    )
+}
+
+// ---
+#[test]
+fn decompress_concatenated_streams() -> TestResult {
+    run(&["-z", "-n", ALL_GZ], "tests/expected/all.n.out")
+}
+
+// ---
+#[test]
+fn decompress_passes_plain_files_through() -> TestResult {
+    run(&["-z", FOX, SPIDERS, BUSTLE], "tests/expected/all.out")?;
+    run(
+        &["-z", "-n", FOX, SPIDERS, BUSTLE],
+        "tests/expected/all.n.out",
+    )
+}
+
+// ---
+#[test]
+fn decompress_passes_bzh_text_through() -> TestResult {
+    // Starts like bzip2, but without a block after the header.
+    run(&["-z", BZH], BZH)
+}
+
+// ---
+#[test]
+fn decompress_empty_bzip2() -> TestResult {
+    run(&["-z", "tests/inputs/empty.txt.bz2"], EMPTY)
+}
+
+// ---
+#[test]
+fn compressed_without_decompress() -> TestResult {
+    run(&[BUSTLE_GZ], BUSTLE_GZ)
+}
+
+// ---
+#[test]
+fn decompress_corrupt() -> TestResult {
+    let output = fs::read_to_string("tests/expected/fox.txt.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["-z", CORRUPT_GZ, FOX])
+        .assert()
+        .failure()
+        .stdout(output)
+        .stderr(predicate::str::is_match(format!("^catr: {CORRUPT_GZ}: "))?);
+    Ok(())
+}
+
+// ---
+#[test]
+fn dies_decompress_and_follow() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-z", "--follow", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn highlight_by_extension() -> TestResult {
+    run(
+        &["--highlight", "--color=always", HELLO_RS],
+        "tests/expected/hello.rs.highlight.out",
+    )
+}
+
+// ---
+#[test]
+fn highlight_language() -> TestResult {
+    run_stdin(
+        HELLO_RS,
+        &["--highlight=rust", "--color=always"],
+        "tests/expected/hello.rs.highlight.out",
+    )
+}
+
+// ---
+#[test]
+fn highlight_n() -> TestResult {
+    run(
+        &["--highlight", "--color=always", "-n", HELLO_RS],
+        "tests/expected/hello.rs.highlight.n.out",
+    )
+}
+
+// ---
+#[test]
+fn highlight_b_show_tabs() -> TestResult {
+    run(
+        &["--highlight", "--color=always", "-bT", HELLO_RS],
+        "tests/expected/hello.rs.highlight.b.T.out",
+    )
+}
+
+// ---
+#[test]
+fn highlight_off_when_not_a_terminal() -> TestResult {
+    // The tests' stdout is a pipe, so auto does not highlight.
+    run(&["--highlight", "-n", FOX], "tests/expected/fox.txt.n.out")?;
+    run(&["--highlight", "--color=auto", HELLO_RS], HELLO_RS)
+}
+
+// ---
+#[test]
+fn highlight_never() -> TestResult {
+    run(&["--highlight=rust", "--color=never", HELLO_RS], HELLO_RS)
+}
+
+// ---
+#[test]
+fn dies_unknown_language() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--highlight=nonsense", HELLO_RS])
+        .assert()
+        .failure()
+        .stderr("catr: unknown language 'nonsense'\n");
+    Ok(())
+}
+
+// ---
+#[test]
+fn dies_color_without_highlight() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--color=always", HELLO_RS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--highlight"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn lines_n() -> TestResult {
+    run(
+        &["-n", "--lines", "2:3", BUSTLE],
+        "tests/expected/the-bustle.txt.n.lines2-3.out",
+    )
+}
+
+// ---
+#[test]
+fn lines_b() -> TestResult {
+    run(
+        &["-b", "--lines=3:6", BUSTLE],
+        "tests/expected/the-bustle.txt.b.lines3-6.out",
+    )
+}
+
+// ---
+#[test]
+fn lines_open_ended() -> TestResult {
+    run(
+        &["--lines", "5:", BUSTLE],
+        "tests/expected/the-bustle.txt.lines5-.out",
+    )?;
+    run_stdin(
+        BUSTLE,
+        &["-n", "--lines", ":3"],
+        "tests/expected/the-bustle.txt.n.lines-3.out",
+    )
+}
+
+// ---
+#[test]
+fn lines_across_files() -> TestResult {
+    run(
+        &["-n", "--lines", "4:6", FOX, SPIDERS, BUSTLE],
+        "tests/expected/all.n.lines4-6.out",
+    )
+}
+
+// ---
+#[test]
+fn lines_per_file() -> TestResult {
+    run(
+        &["--lines", "2:3", "--lines-per-file", FOX, SPIDERS, BUSTLE],
+        "tests/expected/all.lines2-3.per-file.out",
+    )
+}
+
+// ---
+#[test]
+fn lines_s() -> TestResult {
+    run(
+        &[
+            "-ns",
+            "--lines",
+            "6:14",
+            ONE_TWO_THREE_FOUR,
+            ONE_TWO_THREE_FOUR,
+        ],
+        "tests/expected/one-two-three-four.twice.n.s.lines6-14.out",
+    )
+}
+
+// ---
+#[test]
+fn lines_stop_early() -> TestResult {
+    // Once the range is over, the remaining files are never opened.
+    Command::cargo_bin(PRG)?
+        .args(["--lines", "1:1", FOX, &gen_bad_file()])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string(FOX)?);
+    Ok(())
+}
+
+// ---
+#[test]
+fn dies_bad_line_range() -> TestResult {
+    for range in ["5", "0:3", "4:2", "a:b"] {
+        Command::cargo_bin(PRG)?
+            .args(["--lines", range, FOX])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "invalid line range -- {range}"
+            )));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn reverse() -> TestResult {
+    run(
+        &["--reverse", BUSTLE],
+        "tests/expected/the-bustle.txt.reverse.out",
+    )
+}
+
+// ---
+#[test]
+fn reverse_stdin() -> TestResult {
+    run_stdin(
+        BUSTLE,
+        &["--reverse"],
+        "tests/expected/the-bustle.txt.reverse.out",
+    )
+}
+
+// ---
+#[test]
+fn reverse_decompress() -> TestResult {
+    run(
+        &["--reverse", "-z", BUSTLE_GZ],
+        "tests/expected/the-bustle.txt.reverse.out",
+    )
+}
+
+// ---
+#[test]
+fn reverse_no_newline() -> TestResult {
+    run(
+        &["--reverse", NO_NEWLINE],
+        "tests/expected/no-newline.txt.reverse.out",
+    )
+}
+
+// ---
+#[test]
+fn reverse_n_each_file() -> TestResult {
+    run(
+        &["--reverse", "-n", FOX, SPIDERS, BUSTLE],
+        "tests/expected/all.reverse.n.out",
+    )
+}
+
+// ---
+#[test]
+fn reverse_separator() -> TestResult {
+    run(
+        &["--reverse", "--separator", " ", BUSTLE],
+        "tests/expected/the-bustle.txt.reverse.space.out",
+    )
+}
+
+// ---
+#[test]
+fn reverse_regex() -> TestResult {
+    run(
+        &[
+            "--reverse",
+            "--separator",
+            "\\n+",
+            "--regex",
+            ONE_TWO_THREE_FOUR,
+        ],
+        "tests/expected/one-two-three-four.reverse.regex.out",
+    )
+}
+
+// ---
+#[test]
+fn reverse_across_blocks() -> TestResult {
+    // Long enough to be read in several blocks, with lines falling across each boundary.
+    let lines: Vec<String> = (0..100_000).map(|n| format!("line {n}\n")).collect();
+    let mut input = tempfile::NamedTempFile::new()?;
+    std::io::Write::write_all(&mut input, lines.concat().as_bytes())?;
+    let expected: String = lines.iter().rev().map(String::as_str).collect();
+
+    Command::cargo_bin(PRG)?
+        .args(["--reverse", input.path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// ---
+#[test]
+fn dies_empty_separator() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--reverse", "--separator", "", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("separator cannot be empty"));
+    Ok(())
+}
+
+// ---
+#[test]
+fn dies_bad_regex() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--reverse", "--separator", "(", "--regex", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("catr: invalid regex '('"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn show_crlf() -> TestResult {
+    run(
+        &["--show-crlf", CRLF],
+        "tests/expected/crlf.txt.show-crlf.out",
+    )
+}
+
+// ---
+#[test]
+fn crlf_to_lf_b_s() -> TestResult {
+    run(
+        &["--crlf-to-lf", "-bs", CRLF],
+        "tests/expected/crlf.txt.crlf-to-lf.b.s.out",
+    )
+}
+
+// ---
+#[test]
+fn strip_bom_crlf_to_lf() -> TestResult {
+    run_stdin(
+        CRLF,
+        &["--strip-bom", "--crlf-to-lf"],
+        "tests/expected/crlf.txt.strip-bom.crlf-to-lf.out",
+    )
+}
+
+// ---
+#[test]
+fn strip_bom_leaves_other_files() -> TestResult {
+    run(
+        &["--strip-bom", FOX, SPIDERS, BUSTLE],
+        "tests/expected/all.out",
+    )
+}
+
+// ---
+#[test]
+fn to_utf8() -> TestResult {
+    // The little endian file starts with a byte order mark and the big endian one does not.
+    for file in [UTF16LE, UTF16BE] {
+        run(&["--to-utf8", file], "tests/expected/utf16.to-utf8.out")?;
+    }
+    Ok(())
+}
+
+// ---
+#[test]
+fn to_utf8_leaves_utf8() -> TestResult {
+    run(
+        &["--to-utf8", "-n", BUSTLE],
+        "tests/expected/the-bustle.txt.n.out",
+    )
+}
+
+// ---
+#[test]
+fn detect_encoding() -> TestResult {
+    run(
+        &["--detect-encoding", CRLF, UTF16LE, UTF16BE, LATIN1, FOX],
+        "tests/expected/detect-encoding.out",
+    )
+}
+
+// ---
+#[test]
+fn detect_encoding_bad_file() -> TestResult {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["--detect-encoding", &bad, LATIN1])
+        .assert()
+        .failure()
+        .stdout("tests/inputs/latin1.txt: Latin-1\n")
+        .stderr(predicate::str::is_match(format!("^catr: {bad}: "))?);
+    Ok(())
+}
+
+// --------------------------------------------------
+// A catr --follow running in the background, whose output is read as it comes.
+struct Following {
+    child: std::process::Child,
+    output: std::sync::mpsc::Receiver<u8>,
+}
+
+impl Following {
+    fn start(args: &[&str]) -> Result<Self, Box<dyn Error>> {
+        let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin(PRG))
+            .arg("--follow")
+            .args(args)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .spawn()?;
+
+        let stdout = child.stdout.take().ok_or("no stdout")?;
+        let (sender, output) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            use std::io::Read;
+            for byte in std::io::BufReader::new(stdout).bytes().flatten() {
+                if sender.send(byte).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Following { child, output })
+    }
+
+    // Waits for exactly `expected` to be written out.
+    fn expect(&self, expected: &str) -> TestResult {
+        let mut output = Vec::new();
+        while output.len() < expected.len() {
+            output.push(
+                self.output
+                    .recv_timeout(std::time::Duration::from_secs(10))?,
+            );
+        }
+        assert_eq!(String::from_utf8_lossy(&output), expected);
+        Ok(())
+    }
+}
+
+impl Drop for Following {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn append(path: &std::path::Path, contents: &str) -> TestResult {
+    use std::io::Write;
+    fs::OpenOptions::new()
+        .append(true)
+        .open(path)?
+        .write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+// ---
+#[test]
+fn follow_appended_lines() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "one\ntw")?;
+
+    let following = Following::start(&["-n", log.to_str().unwrap()])?;
+    following.expect("     1\tone\n     2\ttw")?;
+    append(&log, "o\nthree\n")?;
+    following.expect("o\n     3\tthree\n")
+}
+
+// ---
+#[test]
+fn follow_truncated() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "one\ntwo\n")?;
+
+    let following = Following::start(&["-n", log.to_str().unwrap()])?;
+    following.expect("     1\tone\n     2\ttwo\n")?;
+    fs::write(&log, "")?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    append(&log, "three\n")?;
+    following.expect("     3\tthree\n")
+}
+
+// ---
+#[test]
+fn follow_replaced() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "one\n")?;
+
+    let following = Following::start(&["-n", log.to_str().unwrap()])?;
+    following.expect("     1\tone\n")?;
+    fs::rename(&log, dir.path().join("log.1"))?;
+    append(&dir.path().join("log.1"), "two\n")?;
+    fs::write(&log, "three\n")?;
+    following.expect("     2\ttwo\n     3\tthree\n")
+}
+
+// ---
+#[test]
+fn follow_ignores_stdin() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--follow", "-"])
+        .write_stdin("one\n")
+        .assert()
+        .success()
+        .stdout("one\n");
+    Ok(())
+}
@@ -0,0 +1,8 @@
+// Says hello.
+fn main() {
+	let name = "world";
+
+	/* Nothing
+	   else. */
+	println!("Hello, {name}!");
+}
@@ -0,0 +1,59 @@
+// The --json output: a line holding one JSON object for each file, in place of the `==> name <==`
+// headers, so that nothing a file name can contain is ambiguous.
+//
+// Every object has the same keys. The head is given as text if it is valid UTF-8, and in base64
+// if not, as `encoding` says. A file that could not be opened has only `name` and `error` set.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+use std::io::{self, Write};
+
+pub fn write_head(
+    output: &mut impl Write,
+    name: &str,
+    head: &[u8],
+    truncated: bool,
+    delimiter: u8,
+) -> io::Result<()> {
+    let (content, encoding) = match std::str::from_utf8(head) {
+        Ok(text) => (Value::from(text), "utf-8"),
+        Err(_) => (Value::from(STANDARD.encode(head)), "base64"),
+    };
+    // A last line without a delimiter still counts as a line.
+    let lines = head.iter().filter(|&&byte| byte == delimiter).count()
+        + usize::from(!head.is_empty() && !head.ends_with(&[delimiter]));
+
+    write_record(
+        output,
+        json!({
+            "name": name,
+            "content": content,
+            "encoding": encoding,
+            "bytes": head.len(),
+            "lines": lines,
+            "truncated": truncated,
+            "error": null,
+        }),
+    )
+}
+
+pub fn write_error(output: &mut impl Write, name: &str, error: &io::Error) -> io::Result<()> {
+    write_record(
+        output,
+        json!({
+            "name": name,
+            "content": null,
+            "encoding": null,
+            "bytes": null,
+            "lines": null,
+            "truncated": null,
+            "error": error.to_string(),
+        }),
+    )
+}
+
+fn write_record(output: &mut impl Write, record: Value) -> io::Result<()> {
+    serde_json::to_writer(&mut *output, &record)?;
+    output.write_all(b"\n")
+}
@@ -1,7 +1,16 @@
 use clap::{Arg, Command};
+use regex::bytes::Regex;
+use std::collections::VecDeque;
 use std::error::Error;
+use std::fmt;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, StdinLock, Write};
+
+mod json;
+mod pool;
+pub mod size;
+
+use size::{parse_size, SizeError};
 
 // head
 // display the first lines of a file.
@@ -13,14 +22,26 @@ use std::io::{self, BufRead, BufReader, Read};
 // -c, --bytes[-]NUM :  print the first NUM bytes of each file; if leading '-', print all but the
 // last NUM bytes of each file
 //
+// NUM may have a multiplier suffix, as in GNU head: b 512, kB 1000, K 1024, MB 1000*1000,
+// M 1024*1024, and so on for G, T, P, E, Z, Y, R and Q. KiB, MiB, ... are the same as K, M, ...
+//
 // -n, --lines=[-]NUM :  print the first NUM lines; if leading, all but last NUM lines
 //
+// --until REGEX :  print the lines before the first line matching REGEX
+//
+// --through REGEX :  print the lines up to and including the first line matching REGEX
+//
+// --preview NUM :  print the first NUM and the last NUM lines, with a line saying how many were left
+// out between them; files of no more than 2*NUM lines are printed whole
+//
 // -q, --quiet, --silent :  don't print headers with file names
 //
 // -v, --verbose :  always print headers with file names
 //
 // -z, --zero-terminated :  lines are deliniated by 'NUL', instead of '\n'
 //
+// --json :  print a JSON object for each file instead of headers (see json.rs)
+//
 // header looks like "===> FILENAME <==="
 
 pub struct Config {
@@ -28,12 +49,17 @@ pub struct Config {
     output_size: usize,
     output_sign: Sign,
     print_headers: bool,
+    json: bool,
+    delimiter: u8,
     files: Vec<Option<String>>,
 }
 
 enum HeadKind {
     Bytes,
     Lines,
+    Until(Regex),
+    Through(Regex),
+    Preview,
 }
 
 #[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Debug)]
@@ -71,6 +97,33 @@ pub fn get_args() -> Result<Config, Box<dyn Error>> {
                 .conflicts_with("bytes")
                 .help("Print the first LINES lines instead of the first 10;\n\tWith the leading '-', print all but the last LINES lines of each file.")
                 .display_order(1))
+        .arg(
+            Arg::new("until")
+                .long("until")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .value_name("REGEX")
+                .conflicts_with_all(&["bytes", "lines", "through"])
+                .help("Print the lines before the first line matching REGEX.")
+                .display_order(5))
+        .arg(
+            Arg::new("through")
+                .long("through")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .value_name("REGEX")
+                .conflicts_with_all(&["bytes", "lines", "until"])
+                .help("Print the lines up to and including the first line matching REGEX.")
+                .display_order(6))
+        .arg(
+            Arg::new("preview")
+                .long("preview")
+                .takes_value(true)
+                .value_name("LINES")
+                .validator(valid_preview_number)
+                .conflicts_with_all(&["bytes", "lines", "until", "through"])
+                .help("Print the first and last LINES lines, with a line saying how many were left out between them.")
+                .display_order(7))
         .arg(
             Arg::new("quiet")
                 .short('q')
@@ -90,7 +143,13 @@ pub fn get_args() -> Result<Config, Box<dyn Error>> {
             Arg::new("zero-terminated")
                 .short('z')
                 .long("zero-terminated")
+                .help("Line delimiter is NUL, not newline.")
                 .display_order(4))
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Print a JSON object for each file, giving its name, head and any error, instead of headers.")
+                .display_order(8))
         .arg(
             Arg::new("file")
                 .takes_value(true)
@@ -100,20 +159,33 @@ pub fn get_args() -> Result<Config, Box<dyn Error>> {
                 .hide_default_value(true))
         .get_matches();
 
+    let regex =
+        |pattern: &str| Regex::new(pattern).map_err(|e| format!("invalid regex '{pattern}': {e}"));
+    // --until and --through have no count of their own, so they are left with the default one,
+    // which is positive and so never skips a file.
     let (output_kind, output) = if matches.is_present("bytes") {
         (HeadKind::Bytes, matches.value_of("bytes").unwrap())
+    } else if let Some(pattern) = matches.value_of("until") {
+        (
+            HeadKind::Until(regex(pattern)?),
+            matches.value_of("lines").unwrap(),
+        )
+    } else if let Some(pattern) = matches.value_of("through") {
+        (
+            HeadKind::Through(regex(pattern)?),
+            matches.value_of("lines").unwrap(),
+        )
+    } else if let Some(count) = matches.value_of("preview") {
+        (HeadKind::Preview, count)
     } else {
         (HeadKind::Lines, matches.value_of("lines").unwrap())
     };
 
     let starts_with_minus = output.as_bytes()[0] == b'-';
-    let output_size = if starts_with_minus {
-        output[1..].parse::<usize>()?
-    } else {
-        output.parse::<usize>()?
-    };
+    let output_size = valid_number(output)?;
 
-    let output_sign = if output_size == 0 {
+    // A preview of no lines still says how many lines were left out.
+    let output_sign = if output_size == 0 && !matches!(output_kind, HeadKind::Preview) {
         Sign::Zero
     } else if starts_with_minus {
         Sign::Negative
@@ -141,34 +213,46 @@ pub fn get_args() -> Result<Config, Box<dyn Error>> {
         output_size,
         output_sign,
         print_headers,
+        json: matches.is_present("json"),
+        delimiter: if matches.is_present("zero-terminated") {
+            b'\0'
+        } else {
+            b'\n'
+        },
         files,
     })
 }
 
 fn valid_byte_number(input: &str) -> Result<(), String> {
-    if valid_number(input).is_ok() {
-        Ok(())
-    } else {
-        Err(format!("illegal byte count -- {input}"))
+    match valid_number(input) {
+        Ok(_) => Ok(()),
+        Err(SizeError::Invalid) => Err(format!("illegal byte count -- {input}")),
+        Err(e) => Err(format!("illegal byte count -- {input}: {e}")),
     }
 }
 
 fn valid_line_number(input: &str) -> Result<(), String> {
-    if valid_number(input).is_ok() {
-        Ok(())
-    } else {
-        Err(format!("illegal line count -- {input}"))
+    match valid_number(input) {
+        Ok(_) => Ok(()),
+        Err(SizeError::Invalid) => Err(format!("illegal line count -- {input}")),
+        Err(e) => Err(format!("illegal line count -- {input}: {e}")),
     }
 }
 
-fn valid_number(input: &str) -> Result<usize, std::num::ParseIntError> {
-    if input.as_bytes()[0] == b'-' {
-        input[1..].parse::<usize>()
-    } else {
-        input.parse::<usize>()
+// Unlike -n, --preview has no use for a leading '-'.
+fn valid_preview_number(input: &str) -> Result<(), String> {
+    match input.starts_with('-') {
+        true => Err(format!("illegal line count -- {input}")),
+        false => valid_line_number(input),
     }
 }
 
+// A count too large to fit in memory is too large to use, even if it fits in a u64.
+fn valid_number(input: &str) -> Result<usize, SizeError> {
+    let size = parse_size(input.strip_prefix('-').unwrap_or(input))?;
+    usize::try_from(size).map_err(|_| SizeError::Overflow)
+}
+
 #[test]
 fn test_valid_number() {
     // Positive Number
@@ -194,72 +278,511 @@ fn test_valid_number() {
     // Non-numeric
     let input = "This_is_not_a_number";
     assert!(valid_number(input).is_err());
+
+    // Suffixed
+    let input = "-2KiB";
+    assert_eq!(valid_number(input).unwrap(), 2048);
+
+    // Too large
+    let input = "18446744073709551616";
+    assert_eq!(valid_number(input), Err(SizeError::Overflow));
+}
+
+// What `run` ends with if any file failed. The files after a failure are still headed, and the
+// failure itself is reported by `report` when its turn comes, so main only needs to exit with 1.
+#[derive(Debug)]
+pub struct InputsFailed;
+
+impl fmt::Display for InputsFailed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "one or more files could not be read")
+    }
 }
 
+impl Error for InputsFailed {}
+
+// Files are opened, and the start of each read, by a pool of threads ahead of their turn, which
+// hides the wait for each one when there are many. Every head is then written straight out in its
+// turn, so that however large it is, no more of it is held in memory than when reading one file.
 pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let mut filenames = config.files.into_iter().peekable();
-    while let Some(filename) = filenames.next() {
-        match open(&filename) {
-            Err(e) => eprintln!("Failed to open {}: {e}", filename.unwrap_or_default()),
-            Ok(file) => {
-                if config.print_headers {
-                    println!("==> {} <==", filename.unwrap_or_default())
+    let mut output = BufWriter::new(io::stdout().lock());
+    let mut first_header = true;
+    let mut failed = false;
+
+    pool::in_order(
+        config.files.len(),
+        |index| read_ahead(&config, &config.files[index]),
+        |index, head| -> Result<(), Box<dyn Error>> {
+            let filename = &config.files[index];
+            let input = match head {
+                Head::Stdin => Ok(open(&None)?),
+                Head::Opened(file) => Ok(Input::File(file)),
+                Head::Unread(e) => Err(e),
+                Head::Unopened(e) => {
+                    failed = true;
+                    if config.json {
+                        let name = name(filename);
+                        json::write_error(&mut output, name, &e)?;
+                    } else {
+                        let message = format!("cannot open {} for reading", quote(filename));
+                        report(&mut output, &message, &e)?;
+                    }
+                    return Ok(());
+                }
+            };
+
+            if config.json {
+                let name = name(filename);
+                match input.and_then(|mut input| take_head(&mut input, &config)) {
+                    Ok(taken) => json::write_head(
+                        &mut output,
+                        name,
+                        &taken.contents,
+                        taken.truncated,
+                        config.delimiter,
+                    )?,
+                    Err(e) => {
+                        failed = true;
+                        json::write_error(&mut output, name, &e)?;
+                    }
+                }
+                return Ok(());
+            }
+
+            // Like GNU head, a blank line separates each header from the file before it.
+            if config.print_headers {
+                let separator = if first_header { "" } else { "\n" };
+                let filename = name(filename);
+                writeln!(output, "{separator}==> {filename} <==")?;
+                first_header = false;
+            }
+            let error = match input {
+                Ok(mut input) => {
+                    let mut tracked = Tracked::new(&mut output);
+                    match write_head(&mut tracked, &mut input, &config) {
+                        Err(e) if tracked.failed => return Err(e.into()),
+                        result => result.err(),
+                    }
                 }
-                match config.output_sign {
-                    Sign::Zero => continue,
-                    Sign::Positive => match config.output_kind {
-                        HeadKind::Bytes => {
-                            let bytes = file
-                                .bytes()
-                                .take(config.output_size)
-                                .map(|byte| byte.unwrap())
-                                .collect::<Vec<_>>();
-
-                            print!("{}", String::from_utf8_lossy(&bytes));
-                        }
-                        HeadKind::Lines => {
-                            for line in file.split(b'\n').take(config.output_size) {
-                                println!("{}", String::from_utf8_lossy(&line?));
-                            }
-                        }
-                    },
-                    Sign::Negative => match config.output_kind {
-                        HeadKind::Bytes => {
-                            let bytes = file.bytes().map(|byte| byte.unwrap()).collect::<Vec<_>>();
-                            let bytes = &bytes[..bytes.len().saturating_sub(config.output_size)];
-
-                            print!("{}", String::from_utf8_lossy(bytes));
-                        }
-                        HeadKind::Lines => {
-                            let lines = file
-                                .split(b'\n')
-                                .map(|line| line.unwrap())
-                                .collect::<Vec<_>>();
-                            let number_of_lines = lines.len();
-                            for line in lines
-                                .into_iter()
-                                .take(number_of_lines.saturating_sub(config.output_size))
-                            {
-                                println!("{}", String::from_utf8_lossy(&line));
-                            }
-                        }
-                    },
-                };
+                Err(e) => Some(e),
+            };
+            if let Some(e) = error {
+                failed = true;
+                report(
+                    &mut output,
+                    &format!("error reading {}", quote(filename)),
+                    &e,
+                )?;
             }
+            Ok(())
+        },
+    )?;
+
+    output.flush()?;
+    match failed {
+        true => Err(Box::new(InputsFailed)),
+        false => Ok(()),
+    }
+}
+
+// How a file is named in headers, messages and --json, as GNU head names it.
+fn name(filename: &Option<String>) -> &str {
+    filename.as_deref().unwrap_or("standard input")
+}
+
+fn quote(filename: &Option<String>) -> String {
+    format!("'{}'", name(filename))
+}
+
+// Anything written so far goes out first, so that the message comes after the output before it.
+//
+// The messages are GNU head's, word for word, so the "(os error N)" that Rust puts after an OS
+// error is left off. catr keeps it on purpose: its messages are its own "catr: FILE: reason"
+// rather than a copy of GNU cat's, and its tests have always expected the error number.
+fn report(output: &mut impl Write, message: &str, e: &io::Error) -> io::Result<()> {
+    output.flush()?;
+    let description = e.to_string();
+    let description = match e.raw_os_error() {
+        Some(code) => description
+            .strip_suffix(&format!(" (os error {code})"))
+            .unwrap_or(&description),
+        None => &description,
+    };
+    eprintln!("headr: {message}: {description}");
+    Ok(())
+}
+
+// Passes writes on to `output`, remembering whether any failed, so that an error while writing out
+// a head as it is read can be told apart from one while reading it.
+struct Tracked<'a, W> {
+    output: &'a mut W,
+    failed: bool,
+}
+
+impl<'a, W: Write> Tracked<'a, W> {
+    fn new(output: &'a mut W) -> Self {
+        Tracked {
+            output,
+            failed: false,
         }
-        if config.print_headers
-            && filenames.peek().is_some()
-            && open(filenames.peek().unwrap()).is_ok()
+    }
+
+    fn track<T>(&mut self, result: io::Result<T>) -> io::Result<T> {
+        if result
+            .as_ref()
+            .is_err_and(|e| e.kind() != io::ErrorKind::Interrupted)
         {
-            println!()
+            self.failed = true;
+        }
+        result
+    }
+}
+
+impl<W: Write> Write for Tracked<'_, W> {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        let result = self.output.write(buffer);
+        self.track(result)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.output.flush();
+        self.track(result)
+    }
+}
+
+enum Head {
+    Stdin,
+    Unopened(io::Error),
+    // Opened, but the first read failed.
+    Unread(io::Error),
+    Opened(BufReader<File>),
+}
+
+// Only the start of a file is read ahead, into its own buffer, so that the files waiting their
+// turn hold no more than BLOCK_SIZE each in memory. Stdin can only be read in turn, and nothing is
+// read for a head of nothing, as then e.g. a directory is not an error.
+fn read_ahead(config: &Config, filename: &Option<String>) -> Head {
+    let Some(filename) = filename else {
+        return Head::Stdin;
+    };
+    match File::open(filename) {
+        Err(e) => Head::Unopened(e),
+        Ok(file) => {
+            let mut file = BufReader::with_capacity(BLOCK_SIZE, file);
+            if config.output_sign == Sign::Zero {
+                return Head::Opened(file);
+            }
+            match file.fill_buf().map(|_| ()) {
+                Err(e) if e.kind() != io::ErrorKind::Interrupted => Head::Unread(e),
+                _ => Head::Opened(file),
+            }
+        }
+    }
+}
+
+// --json needs the whole head in memory, to put it inside a JSON string.
+struct Taken {
+    contents: Vec<u8>,
+    truncated: bool,
+}
+
+fn take_head(input: &mut Input, config: &Config) -> io::Result<Taken> {
+    let mut contents = Vec::new();
+    let left_out = write_head(&mut contents, input, config)?;
+    Ok(Taken {
+        contents,
+        truncated: left_out || !input.reader().fill_buf()?.is_empty(),
+    })
+}
+
+// Returns whether anything that was read was left out of the head. Otherwise, the head was cut
+// short only if there is more input after it.
+fn write_head(output: &mut impl Write, input: &mut Input, config: &Config) -> io::Result<bool> {
+    match config.output_sign {
+        Sign::Zero => Ok(false),
+        Sign::Positive => match &config.output_kind {
+            HeadKind::Bytes => {
+                io::copy(&mut input.reader().take(config.output_size as u64), output)?;
+                Ok(false)
+            }
+            HeadKind::Lines => {
+                write_first_lines(output, input, config.output_size, config.delimiter)?;
+                Ok(false)
+            }
+            HeadKind::Until(regex) => {
+                write_until_match(output, input, regex, false, config.delimiter)
+            }
+            HeadKind::Through(regex) => {
+                write_until_match(output, input, regex, true, config.delimiter)
+            }
+            HeadKind::Preview => write_preview(output, input, config.output_size, config.delimiter),
+        },
+        Sign::Negative => match &config.output_kind {
+            HeadKind::Bytes => write_all_but_last_bytes(output, input, config.output_size),
+            HeadKind::Lines => {
+                write_all_but_last_lines(output, input, config.output_size, config.delimiter)
+            }
+            HeadKind::Until(_) | HeadKind::Through(_) | HeadKind::Preview => {
+                unreachable!("--until, --through and --preview always have a positive count")
+            }
+        },
+    }
+}
+
+// Regular files are kept apart from everything else, as they can be seeked around in to find where
+// the output ends without reading the whole file first.
+pub enum Input {
+    Stdin(BufReader<StdinLock<'static>>),
+    File(BufReader<File>),
+}
+
+impl Input {
+    fn reader(&mut self) -> &mut dyn BufRead {
+        match self {
+            Input::Stdin(stdin) => stdin,
+            Input::File(file) => file,
+        }
+    }
+
+    // Files in e.g. /proc say that they are empty whatever they hold, so they are read like a pipe.
+    fn regular_file_length(&mut self) -> io::Result<Option<(&mut BufReader<File>, u64)>> {
+        match self {
+            Input::File(file) => {
+                let metadata = file.get_ref().metadata()?;
+                match metadata.is_file() && metadata.len() > 0 {
+                    true => Ok(Some((file, metadata.len()))),
+                    false => Ok(None),
+                }
+            }
+            Input::Stdin(_) => Ok(None),
+        }
+    }
+}
+
+const BLOCK_SIZE: usize = 64 * 1024;
+
+fn write_first_lines(
+    output: &mut impl Write,
+    input: &mut Input,
+    count: usize,
+    delimiter: u8,
+) -> io::Result<()> {
+    let mut line = Vec::new();
+    for _ in 0..count {
+        line.clear();
+        if input.reader().read_until(delimiter, &mut line)? == 0 {
+            break;
+        }
+        output.write_all(&line)?;
+    }
+    Ok(())
+}
+
+// The regex is matched against each line without its delimiter, so that e.g. `ERROR$` matches a line
+// ending in ERROR. Nothing after the matching line is read. Returns whether the matching line was
+// left out.
+fn write_until_match(
+    output: &mut impl Write,
+    input: &mut Input,
+    regex: &Regex,
+    through: bool,
+    delimiter: u8,
+) -> io::Result<bool> {
+    let reader = input.reader();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(delimiter, &mut line)? == 0 {
+            return Ok(false);
+        }
+        let contents = line.strip_suffix(&[delimiter]).unwrap_or(&line);
+        if regex.is_match(contents) {
+            if through {
+                output.write_all(&line)?;
+            }
+            return Ok(!through);
+        }
+        output.write_all(&line)?;
+    }
+}
+
+fn write_all_but_last_bytes(
+    output: &mut impl Write,
+    input: &mut Input,
+    count: usize,
+) -> io::Result<bool> {
+    if let Some((file, length)) = input.regular_file_length()? {
+        let head = length.saturating_sub(count as u64);
+        io::copy(&mut file.take(head), output)?;
+        return Ok(head < length);
+    }
+
+    // Only the last `count` bytes read so far are held back, as any before them are certainly not
+    // among the last `count` bytes of the input.
+    let reader = input.reader();
+    let mut held_back = VecDeque::new();
+    let mut buffer = vec![0; BLOCK_SIZE];
+    loop {
+        let bytes = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(!held_back.is_empty()),
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        held_back.extend(&buffer[..bytes]);
+
+        let excess = held_back.len().saturating_sub(count);
+        let (front, back) = held_back.as_slices();
+        let from_front = excess.min(front.len());
+        output.write_all(&front[..from_front])?;
+        output.write_all(&back[..excess - from_front])?;
+        held_back.drain(..excess);
+    }
+}
+
+// Each line keeps its delimiter, if it has one, so that it is written out exactly as it was read.
+fn write_all_but_last_lines(
+    output: &mut impl Write,
+    input: &mut Input,
+    count: usize,
+    delimiter: u8,
+) -> io::Result<bool> {
+    if let Some((file, length)) = input.regular_file_length()? {
+        let head = start_of_last_lines(file, length, count, delimiter)?;
+        file.seek(SeekFrom::Start(0))?;
+        io::copy(&mut file.take(head), output)?;
+        return Ok(head < length);
+    }
+
+    let reader = input.reader();
+    let mut held_back = VecDeque::new();
+    loop {
+        let mut line = Vec::new();
+        if reader.read_until(delimiter, &mut line)? == 0 {
+            return Ok(!held_back.is_empty());
+        }
+        held_back.push_back(line);
+        if held_back.len() > count {
+            if let Some(line) = held_back.pop_front() {
+                output.write_all(&line)?;
+            }
+        }
+    }
+}
+
+// Like -n -NUM after -n NUM, but with the lines in between counted rather than written, and the
+// last lines written after them. In a regular file the last lines are found by reading backwards
+// from the end, as for -n -NUM, so only the lines in between have to be read forwards to be counted.
+// Returns whether any lines were left out.
+fn write_preview(
+    output: &mut impl Write,
+    input: &mut Input,
+    count: usize,
+    delimiter: u8,
+) -> io::Result<bool> {
+    write_first_lines(output, input, count, delimiter)?;
+
+    let omitted = if let Some((file, length)) = input.regular_file_length()? {
+        let start = file.stream_position()?;
+        let tail = match count {
+            0 => length,
+            _ => start_of_last_lines(file, length, count, delimiter)?.max(start),
+        };
+        file.seek(SeekFrom::Start(start))?;
+        let omitted = count_lines(&mut file.take(tail - start), delimiter)?;
+        write_omitted(output, omitted, delimiter)?;
+        io::copy(file, output)?;
+        omitted
+    } else {
+        let reader = input.reader();
+        let mut held_back = VecDeque::new();
+        let mut omitted = 0;
+        loop {
+            let mut line = Vec::new();
+            if reader.read_until(delimiter, &mut line)? == 0 {
+                break;
+            }
+            held_back.push_back(line);
+            if held_back.len() > count {
+                held_back.pop_front();
+                omitted += 1;
+            }
+        }
+        write_omitted(output, omitted, delimiter)?;
+        for line in held_back {
+            output.write_all(&line)?;
         }
+        omitted
+    };
+
+    Ok(omitted > 0)
+}
+
+fn write_omitted(output: &mut impl Write, omitted: u64, delimiter: u8) -> io::Result<()> {
+    if omitted > 0 {
+        write!(output, "… ({omitted} lines omitted) …")?;
+        output.write_all(&[delimiter])?;
     }
     Ok(())
 }
 
-pub fn open(filename: &Option<String>) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+// A last line without a delimiter still counts as a line.
+fn count_lines(input: &mut impl Read, delimiter: u8) -> io::Result<u64> {
+    let mut lines = 0;
+    let mut last = delimiter;
+    let mut buffer = vec![0; BLOCK_SIZE];
+    loop {
+        let bytes = match input.read(&mut buffer) {
+            Ok(0) => return Ok(lines + u64::from(last != delimiter)),
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        lines += buffer[..bytes]
+            .iter()
+            .filter(|&&byte| byte == delimiter)
+            .count() as u64;
+        last = buffer[bytes - 1];
+    }
+}
+
+// Reads backwards from the end of `file` to find where the last `count` lines start. A final line
+// without a delimiter still counts as a line.
+fn start_of_last_lines(
+    file: &mut BufReader<File>,
+    length: u64,
+    count: usize,
+    delimiter: u8,
+) -> io::Result<u64> {
+    let mut lines = 0;
+    let mut end = length;
+    let mut buffer = vec![0; BLOCK_SIZE];
+
+    while end > 0 {
+        let start = end.saturating_sub(BLOCK_SIZE as u64);
+        let block = &mut buffer[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(block)?;
+
+        for (offset, &byte) in block.iter().enumerate().rev() {
+            let position = start + offset as u64;
+            // A delimiter right at the end only finishes the last line, rather than starting another.
+            if byte == delimiter && position + 1 < length {
+                lines += 1;
+                if lines == count {
+                    return Ok(position + 1);
+                }
+            }
+        }
+        end = start;
+    }
+
+    Ok(0)
+}
+
+pub fn open(filename: &Option<String>) -> io::Result<Input> {
     match filename {
-        None => Ok(Box::new(BufReader::new(io::stdin().lock()))),
-        Some(filename) => Ok(Box::new(BufReader::new(File::open(filename)?))),
+        None => Ok(Input::Stdin(BufReader::new(io::stdin().lock()))),
+        Some(filename) => Ok(Input::File(BufReader::new(File::open(filename)?))),
     }
 }
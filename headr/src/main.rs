@@ -2,7 +2,9 @@ use std::process::ExitCode;
 
 fn main() -> ExitCode {
     if let Err(e) = headr::get_args().and_then(headr::run) {
-        eprintln!("{e}");
+        if !e.is::<headr::InputsFailed>() {
+            eprintln!("headr: {e}");
+        }
         ExitCode::FAILURE
     } else {
         ExitCode::SUCCESS
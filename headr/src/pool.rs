@@ -0,0 +1,156 @@
+// Running jobs on a pool of threads while handling their results in order, so that many files can
+// be read at once and still be written out in the order they were given.
+//
+// Jobs are started in order, and only while the result being waited for is fewer than READ_AHEAD
+// jobs back, so however long one job takes, no more than that many results are held in memory.
+
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Condvar, Mutex, PoisonError};
+use std::thread;
+
+const READ_AHEAD: usize = 64;
+
+struct Progress {
+    started: usize,
+    handled: usize,
+    // Set once results are no longer wanted, e.g. after an error.
+    stopped: bool,
+}
+
+struct Queue {
+    jobs: usize,
+    progress: Mutex<Progress>,
+    changed: Condvar,
+}
+
+impl Queue {
+    // Waits until the next job is allowed to start, and returns None once there are none left.
+    fn next(&self) -> Option<usize> {
+        let mut progress = self.progress.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            if progress.stopped || progress.started == self.jobs {
+                return None;
+            }
+            if progress.started < progress.handled + READ_AHEAD {
+                progress.started += 1;
+                return Some(progress.started - 1);
+            }
+            progress = self
+                .changed
+                .wait(progress)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Progress)) {
+        change(&mut self.progress.lock().unwrap_or_else(PoisonError::into_inner));
+        self.changed.notify_all();
+    }
+}
+
+// Calls `work` on each of 0..jobs, spread over as many threads as there are processors, and calls
+// `handle` on this thread with each result in turn. The first error from `handle` stops any more
+// jobs from being started and is returned.
+pub fn in_order<T: Send, E>(
+    jobs: usize,
+    work: impl Fn(usize) -> T + Sync,
+    mut handle: impl FnMut(usize, T) -> Result<(), E>,
+) -> Result<(), E> {
+    let queue = Queue {
+        jobs,
+        progress: Mutex::new(Progress {
+            started: 0,
+            handled: 0,
+            stopped: false,
+        }),
+        changed: Condvar::new(),
+    };
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(jobs);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let sender = sender.clone();
+            let (queue, work) = (&queue, &work);
+            scope.spawn(move || {
+                while let Some(job) = queue.next() {
+                    if sender.send((job, work(job))).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let result = handle_in_order(&queue, receiver, &mut handle);
+        queue.update(|progress| progress.stopped = true);
+        result
+    })
+}
+
+fn handle_in_order<T, E>(
+    queue: &Queue,
+    receiver: Receiver<(usize, T)>,
+    handle: &mut impl FnMut(usize, T) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut finished = HashMap::new();
+    for job in 0..queue.jobs {
+        let result = loop {
+            if let Some(result) = finished.remove(&job) {
+                break result;
+            }
+            match receiver.recv() {
+                Ok((job, result)) => {
+                    finished.insert(job, result);
+                }
+                // Only once every worker has gone, which before the end means one panicked. The
+                // panic is passed on when the threads are joined.
+                Err(_) => return Ok(()),
+            }
+        };
+        handle(job, result)?;
+        queue.update(|progress| progress.handled += 1);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_in_order() {
+    // Later jobs finish first, but are still handled in order.
+    let mut handled = Vec::new();
+    let result: Result<(), ()> = in_order(
+        200,
+        |job| {
+            thread::sleep(std::time::Duration::from_micros(200 - job as u64));
+            job * 2
+        },
+        |job, result| {
+            assert_eq!(result, job * 2);
+            handled.push(job);
+            Ok(())
+        },
+    );
+    assert_eq!(result, Ok(()));
+    assert_eq!(handled, (0..200).collect::<Vec<_>>());
+
+    // An error stops everything after it.
+    let mut handled = 0;
+    let result = in_order(
+        1000,
+        |job| job,
+        |job, _| {
+            handled += 1;
+            if job == 10 {
+                Err(job)
+            } else {
+                Ok(())
+            }
+        },
+    );
+    assert_eq!(result, Err(10));
+    assert_eq!(handled, 11);
+}
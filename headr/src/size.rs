@@ -0,0 +1,127 @@
+// Parsing counts with size suffixes, as GNU coreutils does for e.g. head -c and head -n.
+//
+// A count is a decimal number, optionally followed by a suffix that multiplies it: b is 512 (a
+// block), and K, M, G, T, P, E, Z, Y, R and Q are powers of 1024 (k and m work too). Putting iB
+// after the letter changes nothing, while putting B (or D) after it makes it a power of 1000
+// instead, so 1KiB is 1024 and 1kB is 1000. A suffix on its own counts one of it, so K is 1024.
+//
+// Nothing here is specific to head, so other tools can use it through `headr::size`.
+
+use std::error::Error;
+use std::fmt;
+
+const SUFFIXES: &str = "bkKmMGTPEZYRQ";
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SizeError {
+    // Not a number, or a number with something after it that is not a suffix.
+    Invalid,
+    // A number too large for a u64, either as written or once multiplied by its suffix.
+    Overflow,
+}
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SizeError::Invalid => write!(f, "Invalid argument"),
+            SizeError::Overflow => write!(f, "Value too large for defined data type"),
+        }
+    }
+}
+
+impl Error for SizeError {}
+
+pub fn parse_size(input: &str) -> Result<u64, SizeError> {
+    // Like strtoumax, leading whitespace and a '+' are allowed, but a '-' is not.
+    let input = input.trim_start_matches([' ', '\t', '\n', '\x0b', '\x0c', '\r']);
+    if input.starts_with('-') {
+        return Err(SizeError::Invalid);
+    }
+    let input = input.strip_prefix('+').unwrap_or(input);
+
+    let digits = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, suffix) = input.split_at(digits);
+
+    let mut letters = suffix.chars();
+    let letter = letters.next();
+    if letter.is_some_and(|letter| !SUFFIXES.contains(letter)) {
+        return Err(SizeError::Invalid);
+    }
+
+    let (number, too_long) = match number {
+        "" if letter.is_some() => (1, false),
+        "" => return Err(SizeError::Invalid),
+        number => match number.parse::<u64>() {
+            Ok(number) => (number, false),
+            Err(_) => (u64::MAX, true),
+        },
+    };
+
+    let letter = match letter {
+        Some(letter) => letter,
+        None if too_long => return Err(SizeError::Overflow),
+        None => return Ok(number),
+    };
+
+    let base: u64 = match letters.as_str() {
+        "" | "iB" => 1024,
+        "B" | "D" => 1000,
+        _ => return Err(SizeError::Invalid),
+    };
+    let multiplier = match letter {
+        'b' => Some(512),
+        'k' | 'K' => base.checked_pow(1),
+        'm' | 'M' => base.checked_pow(2),
+        'G' => base.checked_pow(3),
+        'T' => base.checked_pow(4),
+        'P' => base.checked_pow(5),
+        'E' => base.checked_pow(6),
+        'Z' => base.checked_pow(7),
+        'Y' => base.checked_pow(8),
+        'R' => base.checked_pow(9),
+        _ => base.checked_pow(10),
+    };
+
+    match multiplier.and_then(|multiplier| number.checked_mul(multiplier)) {
+        Some(size) if !too_long => Ok(size),
+        _ => Err(SizeError::Overflow),
+    }
+}
+
+#[test]
+fn test_parse_size() {
+    assert_eq!(parse_size("10"), Ok(10));
+    assert_eq!(parse_size("+10"), Ok(10));
+    assert_eq!(parse_size(" 10"), Ok(10));
+    assert_eq!(parse_size("5b"), Ok(2560));
+    assert_eq!(parse_size("2k"), Ok(2048));
+    assert_eq!(parse_size("10K"), Ok(10240));
+    assert_eq!(parse_size("1KiB"), Ok(1024));
+    assert_eq!(parse_size("1kB"), Ok(1000));
+    assert_eq!(parse_size("1KB"), Ok(1000));
+    assert_eq!(parse_size("1MiB"), Ok(1024 * 1024));
+    assert_eq!(parse_size("1MB"), Ok(1000 * 1000));
+    assert_eq!(parse_size("3G"), Ok(3 << 30));
+    assert_eq!(parse_size("1E"), Ok(1 << 60));
+    assert_eq!(parse_size("K"), Ok(1024));
+    assert_eq!(parse_size("18446744073709551615"), Ok(u64::MAX));
+
+    assert_eq!(parse_size(""), Err(SizeError::Invalid));
+    assert_eq!(parse_size("-1"), Err(SizeError::Invalid));
+    assert_eq!(parse_size("1x"), Err(SizeError::Invalid));
+    assert_eq!(parse_size("1g"), Err(SizeError::Invalid));
+    assert_eq!(parse_size("1Ki"), Err(SizeError::Invalid));
+    assert_eq!(parse_size("1KBB"), Err(SizeError::Invalid));
+    assert_eq!(parse_size("0x10"), Err(SizeError::Invalid));
+
+    assert_eq!(parse_size("18446744073709551616"), Err(SizeError::Overflow));
+    assert_eq!(parse_size("16E"), Err(SizeError::Overflow));
+    assert_eq!(parse_size("1Z"), Err(SizeError::Overflow));
+    assert_eq!(parse_size("1Q"), Err(SizeError::Overflow));
+    assert_eq!(
+        parse_size("99999999999999999999K"),
+        Err(SizeError::Overflow)
+    );
+}
@@ -1,20 +1,27 @@
-use assert_cmd::Command;
+// The tests keep the slice borrows they were written with, as in the other crates' tests.
+#![allow(clippy::needless_borrows_for_generic_args)]
+
+use assert_cmd::{assert::OutputAssertExt, cargo::CommandCargoExt, Command};
 use predicates::prelude::*;
 use rand::{distributions::Alphanumeric, Rng};
-use std::io::prelude::*;
-use std::{
-    error::Error,
-    fs::{self, File},
-};
+use std::{error::Error, fs};
 
 type TestResult = Result<(), Box<dyn Error>>;
 
 const PRG: &str = "headr";
+const INPUTS: &str = "./tests/inputs";
 const EMPTY: &str = "./tests/inputs/empty.txt";
 const ONE: &str = "./tests/inputs/one.txt";
 const TWO: &str = "./tests/inputs/two.txt";
 const THREE: &str = "./tests/inputs/three.txt";
 const TEN: &str = "./tests/inputs/ten.txt";
+const ZERO: &str = "./tests/inputs/zero.txt";
+const NO_NEWLINE: &str = "./tests/inputs/no-newline.txt";
+const INVALID: &str = "./tests/inputs/invalid-utf8.txt";
+const LONG: &str = "./tests/inputs/long.txt";
+const LOG: &str = "./tests/inputs/log.txt";
+const LOG_ZERO: &str = "./tests/inputs/log-zero.txt";
+const ODD: &str = "./tests/inputs/odd \"name\" <==.txt";
 
 // --------------------------------------------------
 fn random_string() -> String {
@@ -63,6 +70,38 @@ fn dies_bad_lines() -> TestResult {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn dies_bad_suffix() -> TestResult {
+    for bad in ["1x", "1g", "1Ki", "1KBB", "-1x"] {
+        Command::cargo_bin(PRG)?
+            .args(["-c", bad, EMPTY])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "illegal byte count -- {bad}"
+            )));
+    }
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_too_large() -> TestResult {
+    for bad in ["99999999999999999999", "20E", "1Z", "-1Q"] {
+        Command::cargo_bin(PRG)?
+            .args(["-n", bad, EMPTY])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "illegal line count -- {bad}: Value too large for defined data type"
+            )));
+    }
+
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn dies_bytes_and_lines() -> TestResult {
@@ -78,50 +117,177 @@ fn dies_bytes_and_lines() -> TestResult {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn dies_bad_regex() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--until", "(", LOG])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid regex '('"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_until_and_lines() -> TestResult {
+    let msg = "The argument '--until <REGEX>' cannot be used with '--lines <[-]LINES>'";
+
+    Command::cargo_bin(PRG)?
+        .args(["--until", "x", "-n", "2", LOG])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(msg));
+
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn skips_bad_file() -> TestResult {
     let bad = gen_bad_file();
-    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    let expected = format!("headr: cannot open '{bad}' for reading: No such file or directory\n");
     Command::cargo_bin(PRG)?
         .args([EMPTY, &bad, ONE])
         .assert()
-        .stderr(predicate::str::is_match(expected)?);
+        .failure()
+        .code(1)
+        .stderr(expected);
 
     Ok(())
 }
 
 // --------------------------------------------------
-fn run(args: &[&str], expected_file: &str) -> TestResult {
-    // Extra work here due to lossy UTF
-    let mut file = File::open(expected_file)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let expected = String::from_utf8_lossy(&buffer);
+#[test]
+fn skips_directory() -> TestResult {
+    let expected = fs::read("tests/expected/dir-one.out")?;
+    Command::cargo_bin(PRG)?
+        .args([INPUTS, ONE])
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(expected)
+        .stderr("headr: error reading './tests/inputs': Is a directory\n");
 
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn zero_count_directory() -> TestResult {
+    // As with GNU head, nothing is read for a head of nothing, so a directory is not an error.
+    for count in ["-n", "-c"] {
+        Command::cargo_bin(PRG)?
+            .args([count, "0", INPUTS])
+            .assert()
+            .success()
+            .stdout("")
+            .stderr("");
+    }
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_reading_directory() -> TestResult {
+    for args in [["-c", "-3"], ["-n", "-3"], ["-c", "3"], ["--preview", "1"]] {
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .arg(INPUTS)
+            .assert()
+            .failure()
+            .code(1)
+            .stdout("")
+            .stderr("headr: error reading './tests/inputs': Is a directory\n");
+    }
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_reading_directory_stdin() -> TestResult {
+    // A directory as stdin can only be given by hand, rather than through assert_cmd.
+    std::process::Command::cargo_bin(PRG)?
+        .stdin(fs::File::open(INPUTS)?)
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("headr: error reading 'standard input': Is a directory\n");
+
+    Ok(())
+}
+
+// --------------------------------------------------
+// The peak memory of headr, in KiB, run with `args` and its output thrown away.
+#[cfg(target_os = "linux")]
+fn max_rss(args: &[&str]) -> Result<i64, Box<dyn Error>> {
+    let child = std::process::Command::cargo_bin(PRG)?
+        .args(args)
+        .stdout(std::process::Stdio::null())
+        .spawn()?;
+    let mut status = 0;
+    let mut usage = unsafe { std::mem::zeroed::<libc::rusage>() };
+    let pid = unsafe { libc::wait4(child.id() as i32, &mut status, 0, &mut usage) };
+    assert_eq!(pid, child.id() as i32);
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+    Ok(usage.ru_maxrss)
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn negative_counts_do_not_buffer_file() -> TestResult {
+    // A sparse file takes no room on disk, but all of it is head.
+    let file = tempfile::NamedTempFile::new()?;
+    file.as_file().set_len(128 << 20)?;
+    let path = file.path().to_str().unwrap();
+
+    for args in [["-c", "-1"], ["-n", "-1"]] {
+        let max_rss = max_rss(&[args[0], args[1], path, ONE])?;
+        assert!(max_rss < 32 << 10, "{args:?} used {max_rss} KiB");
+    }
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn json_fails_on_bad_file() -> TestResult {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["--json", ONE, &bad])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("");
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
     Command::cargo_bin(PRG)?
         .args(args)
         .assert()
         .success()
-        .stdout(predicate::eq(&expected.as_bytes() as &[u8]));
+        .stdout(predicate::eq(&expected as &[u8]));
 
     Ok(())
 }
 
 // --------------------------------------------------
 fn run_stdin(args: &[&str], input_file: &str, expected_file: &str) -> TestResult {
-    // Extra work here due to lossy UTF
-    let mut file = File::open(expected_file)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let expected = String::from_utf8_lossy(&buffer);
-    let input = fs::read_to_string(input_file)?;
+    let expected = fs::read(expected_file)?;
+    let input = fs::read(input_file)?;
 
     Command::cargo_bin(PRG)?
         .write_stdin(input)
         .args(args)
         .assert()
-        .stdout(predicate::eq(&expected.as_bytes() as &[u8]));
+        .stdout(predicate::eq(&expected as &[u8]));
 
     Ok(())
 }
@@ -414,3 +580,548 @@ fn multiple_files_c4() -> TestResult {
         "tests/expected/all.c4.out",
     )
 }
+
+// --------------------------------------------------
+// Negative counts: everything but the last LINES lines or BYTES bytes
+
+#[test]
+fn one_n_minus2() -> TestResult {
+    run(&[ONE, "-n", "-2"], "tests/expected/one.txt.n-2.out")
+}
+
+#[test]
+fn one_n_minus2_stdin() -> TestResult {
+    run_stdin(&["-n", "-2"], ONE, "tests/expected/one.txt.n-2.out")
+}
+
+#[test]
+fn one_n_minus4() -> TestResult {
+    run(&[ONE, "-n", "-4"], "tests/expected/one.txt.n-4.out")
+}
+
+#[test]
+fn one_n_minus4_stdin() -> TestResult {
+    run_stdin(&["-n", "-4"], ONE, "tests/expected/one.txt.n-4.out")
+}
+
+#[test]
+fn one_c_minus2() -> TestResult {
+    run(&[ONE, "-c", "-2"], "tests/expected/one.txt.c-2.out")
+}
+
+#[test]
+fn one_c_minus2_stdin() -> TestResult {
+    run_stdin(&["-c", "-2"], ONE, "tests/expected/one.txt.c-2.out")
+}
+
+#[test]
+fn one_c_minus4() -> TestResult {
+    run(&[ONE, "-c", "-4"], "tests/expected/one.txt.c-4.out")
+}
+
+#[test]
+fn one_c_minus4_stdin() -> TestResult {
+    run_stdin(&["-c", "-4"], ONE, "tests/expected/one.txt.c-4.out")
+}
+
+#[test]
+fn two_n_minus2() -> TestResult {
+    run(&[TWO, "-n", "-2"], "tests/expected/two.txt.n-2.out")
+}
+
+#[test]
+fn two_n_minus2_stdin() -> TestResult {
+    run_stdin(&["-n", "-2"], TWO, "tests/expected/two.txt.n-2.out")
+}
+
+#[test]
+fn two_n_minus4() -> TestResult {
+    run(&[TWO, "-n", "-4"], "tests/expected/two.txt.n-4.out")
+}
+
+#[test]
+fn two_n_minus4_stdin() -> TestResult {
+    run_stdin(&["-n", "-4"], TWO, "tests/expected/two.txt.n-4.out")
+}
+
+#[test]
+fn two_c_minus2() -> TestResult {
+    run(&[TWO, "-c", "-2"], "tests/expected/two.txt.c-2.out")
+}
+
+#[test]
+fn two_c_minus2_stdin() -> TestResult {
+    run_stdin(&["-c", "-2"], TWO, "tests/expected/two.txt.c-2.out")
+}
+
+#[test]
+fn two_c_minus4() -> TestResult {
+    run(&[TWO, "-c", "-4"], "tests/expected/two.txt.c-4.out")
+}
+
+#[test]
+fn two_c_minus4_stdin() -> TestResult {
+    run_stdin(&["-c", "-4"], TWO, "tests/expected/two.txt.c-4.out")
+}
+
+#[test]
+fn three_n_minus2() -> TestResult {
+    run(&[THREE, "-n", "-2"], "tests/expected/three.txt.n-2.out")
+}
+
+#[test]
+fn three_n_minus2_stdin() -> TestResult {
+    run_stdin(&["-n", "-2"], THREE, "tests/expected/three.txt.n-2.out")
+}
+
+#[test]
+fn three_n_minus4() -> TestResult {
+    run(&[THREE, "-n", "-4"], "tests/expected/three.txt.n-4.out")
+}
+
+#[test]
+fn three_n_minus4_stdin() -> TestResult {
+    run_stdin(&["-n", "-4"], THREE, "tests/expected/three.txt.n-4.out")
+}
+
+#[test]
+fn three_c_minus2() -> TestResult {
+    run(&[THREE, "-c", "-2"], "tests/expected/three.txt.c-2.out")
+}
+
+#[test]
+fn three_c_minus2_stdin() -> TestResult {
+    run_stdin(&["-c", "-2"], THREE, "tests/expected/three.txt.c-2.out")
+}
+
+#[test]
+fn three_c_minus4() -> TestResult {
+    run(&[THREE, "-c", "-4"], "tests/expected/three.txt.c-4.out")
+}
+
+#[test]
+fn three_c_minus4_stdin() -> TestResult {
+    run_stdin(&["-c", "-4"], THREE, "tests/expected/three.txt.c-4.out")
+}
+
+#[test]
+fn ten_n_minus2() -> TestResult {
+    run(&[TEN, "-n", "-2"], "tests/expected/ten.txt.n-2.out")
+}
+
+#[test]
+fn ten_n_minus2_stdin() -> TestResult {
+    run_stdin(&["-n", "-2"], TEN, "tests/expected/ten.txt.n-2.out")
+}
+
+#[test]
+fn ten_n_minus4() -> TestResult {
+    run(&[TEN, "-n", "-4"], "tests/expected/ten.txt.n-4.out")
+}
+
+#[test]
+fn ten_n_minus4_stdin() -> TestResult {
+    run_stdin(&["-n", "-4"], TEN, "tests/expected/ten.txt.n-4.out")
+}
+
+#[test]
+fn ten_c_minus2() -> TestResult {
+    run(&[TEN, "-c", "-2"], "tests/expected/ten.txt.c-2.out")
+}
+
+#[test]
+fn ten_c_minus2_stdin() -> TestResult {
+    run_stdin(&["-c", "-2"], TEN, "tests/expected/ten.txt.c-2.out")
+}
+
+#[test]
+fn ten_c_minus4() -> TestResult {
+    run(&[TEN, "-c", "-4"], "tests/expected/ten.txt.c-4.out")
+}
+
+#[test]
+fn ten_c_minus4_stdin() -> TestResult {
+    run_stdin(&["-c", "-4"], TEN, "tests/expected/ten.txt.c-4.out")
+}
+
+#[test]
+fn multiple_files_n_minus2() -> TestResult {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TEN, "-n", "-2"],
+        "tests/expected/all.n-2.out",
+    )
+}
+
+#[test]
+fn multiple_files_n_minus4() -> TestResult {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TEN, "-n", "-4"],
+        "tests/expected/all.n-4.out",
+    )
+}
+
+#[test]
+fn multiple_files_c_minus2() -> TestResult {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TEN, "-c", "-2"],
+        "tests/expected/all.c-2.out",
+    )
+}
+
+#[test]
+fn multiple_files_c_minus4() -> TestResult {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TEN, "-c", "-4"],
+        "tests/expected/all.c-4.out",
+    )
+}
+
+// --------------------------------------------------
+// -z: lines end with NUL rather than newline
+#[test]
+fn zero_z() -> TestResult {
+    run(&[ZERO, "-z"], "tests/expected/zero.txt.z.out")
+}
+
+#[test]
+fn zero_z_n2() -> TestResult {
+    run(&[ZERO, "-z", "-n", "2"], "tests/expected/zero.txt.z.n2.out")
+}
+
+#[test]
+fn zero_z_n_minus2() -> TestResult {
+    run(
+        &[ZERO, "-z", "-n", "-2"],
+        "tests/expected/zero.txt.z.n-2.out",
+    )
+}
+
+#[test]
+fn zero_z_n2_stdin() -> TestResult {
+    run_stdin(&["-z", "-n", "2"], ZERO, "tests/expected/zero.txt.z.n2.out")
+}
+
+#[test]
+fn zero_z_n_minus2_stdin() -> TestResult {
+    run_stdin(
+        &["-z", "-n", "-2"],
+        ZERO,
+        "tests/expected/zero.txt.z.n-2.out",
+    )
+}
+
+#[test]
+fn zero_n1_without_z() -> TestResult {
+    run(&[ZERO, "-n", "1"], "tests/expected/zero.txt.n1.out")
+}
+
+#[test]
+fn multiple_files_z_n2() -> TestResult {
+    run(
+        &["--zero-terminated", "-n", "2", ZERO, ZERO],
+        "tests/expected/zero.txt.twice.z.n2.out",
+    )
+}
+
+// --------------------------------------------------
+// Output is byte for byte what was read
+#[test]
+fn no_newline() -> TestResult {
+    run(&[NO_NEWLINE], "tests/expected/no-newline.txt.out")
+}
+
+#[test]
+fn no_newline_n_minus1() -> TestResult {
+    run(
+        &[NO_NEWLINE, "-n", "-1"],
+        "tests/expected/no-newline.txt.n-1.out",
+    )
+}
+
+#[test]
+fn no_newline_stdin() -> TestResult {
+    run_stdin(&[], NO_NEWLINE, "tests/expected/no-newline.txt.out")
+}
+
+#[test]
+fn no_newline_z() -> TestResult {
+    run(&[NO_NEWLINE, "-z"], "tests/expected/no-newline.txt.z.out")
+}
+
+#[test]
+fn invalid_utf8() -> TestResult {
+    run(&[INVALID], "tests/expected/invalid-utf8.txt.out")
+}
+
+#[test]
+fn invalid_utf8_c8() -> TestResult {
+    // Cuts the two byte character on the second line in half.
+    run(
+        &[INVALID, "-c", "8"],
+        "tests/expected/invalid-utf8.txt.c8.out",
+    )
+}
+
+#[test]
+fn invalid_utf8_n_minus1_stdin() -> TestResult {
+    run_stdin(
+        &["-n", "-1"],
+        INVALID,
+        "tests/expected/invalid-utf8.txt.n-1.out",
+    )
+}
+
+#[test]
+fn long_c5b() -> TestResult {
+    run(&[LONG, "-c", "5b"], "tests/expected/long.txt.c5b.out")
+}
+
+#[test]
+fn long_c1k() -> TestResult {
+    run(&[LONG, "-c", "1K"], "tests/expected/long.txt.c1K.out")
+}
+
+#[test]
+fn long_c1kib() -> TestResult {
+    run(&[LONG, "-c", "1KiB"], "tests/expected/long.txt.c1KiB.out")
+}
+
+#[test]
+fn long_c1kb() -> TestResult {
+    run(&[LONG, "-c", "1kB"], "tests/expected/long.txt.c1kB.out")
+}
+
+#[test]
+fn long_c1m() -> TestResult {
+    // More than the whole file, which is all written out.
+    run(&[LONG, "-c", "1M"], LONG)
+}
+
+#[test]
+fn long_c_minus1k_stdin() -> TestResult {
+    run_stdin(&["-c", "-1K"], LONG, "tests/expected/long.txt.c-1K.out")
+}
+
+#[test]
+fn long_n2k() -> TestResult {
+    run(&[LONG, "-n", "2k"], "tests/expected/long.txt.n2k.out")
+}
+
+#[test]
+fn long_n_minus1k() -> TestResult {
+    run(&[LONG, "-n", "-1k"], "tests/expected/long.txt.n-1k.out")
+}
+
+#[test]
+fn long_n_suffix_only() -> TestResult {
+    // A suffix without a number counts one of it.
+    run(&[LONG, "-n", "K"], "tests/expected/long.txt.nK.out")
+}
+
+#[test]
+fn log_until() -> TestResult {
+    run(
+        &[LOG, "--until", "^ERROR"],
+        "tests/expected/log.txt.until.out",
+    )
+}
+
+#[test]
+fn log_through() -> TestResult {
+    run(
+        &[LOG, "--through", "^ERROR"],
+        "tests/expected/log.txt.through.out",
+    )
+}
+
+#[test]
+fn log_until_stdin() -> TestResult {
+    run_stdin(
+        &["--until", "^ERROR"],
+        LOG,
+        "tests/expected/log.txt.until.out",
+    )
+}
+
+#[test]
+fn log_through_no_match() -> TestResult {
+    run(&[TEN, "--through", "^ERROR"], "tests/expected/ten.txt.out")
+}
+
+#[test]
+fn log_three_through() -> TestResult {
+    // The delimiter is not part of what is matched, so `$` matches at the end of the line.
+    run(
+        &[LOG, THREE, "--through", "giving up$"],
+        "tests/expected/log-three.through.out",
+    )
+}
+
+#[test]
+fn log_zero_z_until() -> TestResult {
+    run(
+        &[LOG_ZERO, "-z", "--until", "^ERROR"],
+        "tests/expected/log-zero.txt.z.until.out",
+    )
+}
+
+#[test]
+fn log_zero_z_through() -> TestResult {
+    run(
+        &[LOG_ZERO, "-z", "--through", "^ERROR"],
+        "tests/expected/log-zero.txt.z.through.out",
+    )
+}
+
+#[test]
+fn one_missing_two() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = fs::read("tests/expected/one-missing-two.out")?;
+    Command::cargo_bin(PRG)?
+        .args([ONE, &bad, TWO])
+        .assert()
+        .failure()
+        .stdout(expected);
+
+    Ok(())
+}
+
+#[test]
+fn missing_one_two() -> TestResult {
+    // The first header printed has no blank line before it, even if a file before it failed.
+    let bad = gen_bad_file();
+    let expected = fs::read("tests/expected/missing-one-two.out")?;
+    Command::cargo_bin(PRG)?
+        .args([&bad, ONE, TWO])
+        .assert()
+        .failure()
+        .stdout(expected);
+
+    Ok(())
+}
+
+#[test]
+fn one_two_n0() -> TestResult {
+    run(&["-n", "0", ONE, TWO], "tests/expected/one-two.n0.out")
+}
+
+#[test]
+fn many_n3() -> TestResult {
+    // Enough files to keep every worker busy, which still come out in the order given.
+    let files = [ONE, TEN, THREE, LONG].repeat(50);
+    let args: Vec<&str> = ["-n", "3"].into_iter().chain(files).collect();
+    run(&args, "tests/expected/many.n3.out")
+}
+
+#[test]
+fn json_n2() -> TestResult {
+    run(
+        &["--json", "-n", "2", ONE, TEN, INVALID, EMPTY],
+        "tests/expected/json.n2.out",
+    )
+}
+
+#[test]
+fn json_n_minus2_stdin() -> TestResult {
+    run_stdin(
+        &["--json", "-n", "-2"],
+        THREE,
+        "tests/expected/json.stdin.n-2.out",
+    )
+}
+
+#[test]
+fn json_z_through() -> TestResult {
+    run(
+        &["--json", "-z", "--through", "^ERROR", LOG_ZERO],
+        "tests/expected/json.log-zero.z.through.out",
+    )
+}
+
+#[test]
+fn json_odd_name() -> TestResult {
+    run(&["--json", ODD], "tests/expected/json.odd.out")
+}
+
+#[test]
+fn json_missing() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!(
+        r#"{{"bytes":null,"content":null,"encoding":null,"error":"No such file or directory (os error 2)","lines":null,"name":"{bad}","truncated":null}}"#
+    );
+    Command::cargo_bin(PRG)?
+        .args(["--json", &bad])
+        .assert()
+        .stdout(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+#[test]
+fn ten_preview2() -> TestResult {
+    run(
+        &[TEN, "--preview", "2"],
+        "tests/expected/ten.txt.preview2.out",
+    )
+}
+
+#[test]
+fn ten_preview2_stdin() -> TestResult {
+    run_stdin(
+        &["--preview", "2"],
+        TEN,
+        "tests/expected/ten.txt.preview2.out",
+    )
+}
+
+#[test]
+fn ten_preview5() -> TestResult {
+    // No more than twice as many lines as asked for, so printed whole without a marker.
+    run(
+        &[TEN, "--preview", "5"],
+        "tests/expected/ten.txt.preview5.out",
+    )
+}
+
+#[test]
+fn no_newline_preview0() -> TestResult {
+    run(
+        &[NO_NEWLINE, "--preview", "0"],
+        "tests/expected/no-newline.txt.preview0.out",
+    )
+}
+
+#[test]
+fn log_zero_z_preview1() -> TestResult {
+    run(
+        &[LOG_ZERO, "-z", "--preview", "1"],
+        "tests/expected/log-zero.txt.z.preview1.out",
+    )
+}
+
+#[test]
+fn long_two_preview3() -> TestResult {
+    run(
+        &[LONG, TWO, "--preview", "3"],
+        "tests/expected/long-two.preview3.out",
+    )
+}
+
+#[test]
+fn dies_preview_and_lines() -> TestResult {
+    let msg = "The argument '--preview <LINES>' cannot be used with '--lines <[-]LINES>'";
+
+    Command::cargo_bin(PRG)?
+        .args(["--preview", "2", "-n", "3", TEN])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(msg));
+
+    Ok(())
+}
+
+#[test]
+fn two_v_stdin() -> TestResult {
+    // Stdin has the same name in its header as in error messages.
+    run_stdin(&["-v"], TWO, "tests/expected/two.txt.v.stdin.out")
+}
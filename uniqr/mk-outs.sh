@@ -0,0 +1,53 @@
+#!/usr/bin/env fish
+
+set INDIR "tests/inputs"
+set OUTDIR "tests/expected"
+
+if test ! -d $OUTDIR
+	mkdir -p $OUTDIR
+end
+
+for FILE in $INDIR/*.txt
+	set BASENAME (basename $FILE)
+	uniq $FILE > $OUTDIR/$BASENAME.out
+	uniq -c $FILE > $OUTDIR/$BASENAME.c.out
+	uniq -d $FILE > $OUTDIR/$BASENAME.d.out
+	uniq -D $FILE > $OUTDIR/$BASENAME.D.out
+	uniq -u $FILE > $OUTDIR/$BASENAME.u.out
+	uniq -c -d $FILE > $OUTDIR/$BASENAME.c.d.out
+	uniq -c -u $FILE > $OUTDIR/$BASENAME.c.u.out
+	uniq -d -u $FILE > $OUTDIR/$BASENAME.d.u.out
+	uniq -D -u $FILE > $OUTDIR/$BASENAME.D.u.out
+end
+
+set FIELDS "$INDIR/fields.txt"
+uniq -f 1 $FIELDS > $OUTDIR/fields.txt.f1.out
+uniq -f 1 -i $FIELDS > $OUTDIR/fields.txt.f1.i.out
+uniq -s 20 $FIELDS > $OUTDIR/fields.txt.s20.out
+uniq -s 20 -i $FIELDS > $OUTDIR/fields.txt.s20.i.out
+uniq -f 1 -w 4 -c $FIELDS > $OUTDIR/fields.txt.f1.w4.c.out
+uniq -w 19 -D $FIELDS > $OUTDIR/fields.txt.w19.D.out
+uniq -f 1 -s 1 -i -c $FIELDS > $OUTDIR/fields.txt.f1.s1.i.c.out
+uniq -f 5 $FIELDS > $OUTDIR/fields.txt.f5.out
+uniq -w 0 -u $FIELDS > $OUTDIR/fields.txt.w0.u.out
+
+set THREE "$INDIR/three.txt"
+for METHOD in none prepend separate
+	uniq --all-repeated=$METHOD $THREE > $OUTDIR/three.txt.all-repeated.$METHOD.out
+end
+uniq --all-repeated=separate -u $THREE > $OUTDIR/three.txt.all-repeated.separate.u.out
+for METHOD in separate prepend append both
+	uniq --group=$METHOD $THREE > $OUTDIR/three.txt.group.$METHOD.out
+end
+uniq --group=both -f 1 $FIELDS > $OUTDIR/fields.txt.group.both.f1.out
+uniq --group=both $INDIR/empty.txt > $OUTDIR/empty.txt.group.out
+
+# uniq has no equivalent of --global, so these are built with awk instead.
+set COUNT_FIRST_SEEN '{ if (!($0 in count)) order[n++] = $0; count[$0]++ } END { for (i = 0; i < n; i++) printf "%7d %s\n", count[order[i]], order[i] }'
+awk '!seen[$0]++' $THREE > $OUTDIR/three.txt.global.out
+awk $COUNT_FIRST_SEEN $THREE > $OUTDIR/three.txt.global.c.out
+awk $COUNT_FIRST_SEEN $THREE | awk '$1 > 1 { sub(/^ *[0-9]+ /, ""); print }' > $OUTDIR/three.txt.global.d.out
+awk '!seen[$0]++' $INDIR/skip.txt > $OUTDIR/skip.txt.global.out
+awk $COUNT_FIRST_SEEN $INDIR/skip.txt | awk '$1 == 1 { sub(/^ *[0-9]+ /, ""); print }' > $OUTDIR/skip.txt.global.u.out
+# every repeated key in fields.txt is adjacent, so plain uniq gives the --global result.
+uniq -f 1 -i -c $FIELDS > $OUTDIR/fields.txt.global.f1.i.c.out
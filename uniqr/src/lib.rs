@@ -0,0 +1,512 @@
+use clap::{Arg, Command};
+use std::borrow::Cow;
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::mem;
+
+// uniq
+// report or omit repeated lines.
+// filters adjacent matching lines from INPUT (or stdin), writing to OUTPUT (or stdout).
+// no INPUT or '-' is stdin.
+// matching lines are only merged if they are adjacent; non-adjacent repeats are printed again.
+// every line written is terminated by '\n', even if the final input line was not.
+//
+// -c, --count :  prefix lines by the number of occurrences
+//
+// -d, --repeated :  only print duplicate lines, one for each group
+//
+// -D, --all-repeated[=METHOD] :  print all duplicate lines; groups can be delimited with an empty
+// line. METHOD={none(default),prepend,separate}
+//
+// --group[=METHOD] :  show all items, separating groups with an empty line.
+// METHOD={separate(default),prepend,append,both}
+//
+// -u, --unique :  only print unique lines
+//
+// -f, --skip-fields=N :  avoid comparing the first N fields
+//
+// -i, --ignore-case :  ignore differences in case when comparing
+//
+// -s, --skip-chars=N :  avoid comparing the first N characters
+//
+// -w, --check-chars=N :  compare no more than N characters in lines
+//
+// --global :  merge matching lines anywhere in the input, not only adjacent ones, printing each in
+// the order it was first seen
+//
+// --hash-keys :  with --global, remember a 64-bit hash of each key instead of the key itself
+//
+// a field is a run of blanks (spaces and tabs), then non-blank characters.
+// fields are skipped before characters. characters are counted as bytes, as GNU uniq does.
+//
+// -d, -D and -u combine the way they do in GNU uniq: each one switches off a part of the output,
+// so e.g. '-d -u' prints nothing and '-D -u' prints every repeated line except the first.
+
+#[derive(Clone, Copy)]
+enum Delimit {
+    None,
+    Prepend,
+    Append,
+    Separate,
+    Both,
+}
+
+pub struct Config {
+    in_file: Option<String>,
+    out_file: Option<String>,
+    count: bool,
+    output_unique: bool,
+    output_first_repeated: bool,
+    output_later_repeated: bool,
+    grouping: bool,
+    delimit: Delimit,
+    global: bool,
+    hash_keys: bool,
+    skip_fields: usize,
+    skip_chars: usize,
+    check_chars: Option<usize>,
+    ignore_case: bool,
+}
+
+pub fn get_args() -> Result<Config, Box<dyn Error>> {
+    let matches = Command::new("uniqr")
+        .version("0.1.0")
+        .author("BeniiAlCo")
+        .about("A rust port of uniq -- a tool that filters adjacent matching lines from INPUT (or standard input), writing to OUTPUT (or standard output). With no INPUT, or when INPUT is -, read standard input.")
+        .arg(
+            Arg::new("count")
+                .short('c')
+                .long("count")
+                .help("Prefix lines by the number of occurrences.")
+                .display_order(0))
+        .arg(
+            Arg::new("repeated")
+                .short('d')
+                .long("repeated")
+                .help("Only print duplicate lines, one for each group.")
+                .display_order(1))
+        .arg(
+            Arg::new("all_repeated")
+                .short('D')
+                .long("all-repeated")
+                .takes_value(true)
+                .min_values(0)
+                .max_values(1)
+                .require_equals(true)
+                .value_name("METHOD")
+                .possible_values(["none", "prepend", "separate"])
+                .default_missing_value("none")
+                .hide_possible_values(true)
+                .conflicts_with("count")
+                .help("Print all duplicate lines, delimiting groups with an empty line according to METHOD={none(default),prepend,separate}.")
+                .display_order(2))
+        .arg(
+            Arg::new("unique")
+                .short('u')
+                .long("unique")
+                .help("Only print unique lines.")
+                .display_order(3))
+        .arg(
+            Arg::new("skip_fields")
+                .short('f')
+                .long("skip-fields")
+                .takes_value(true)
+                .value_name("N")
+                .validator(valid_field_number)
+                .help("Avoid comparing the first N fields.")
+                .display_order(4))
+        .arg(
+            Arg::new("ignore_case")
+                .short('i')
+                .long("ignore-case")
+                .help("Ignore differences in case when comparing.")
+                .display_order(5))
+        .arg(
+            Arg::new("skip_chars")
+                .short('s')
+                .long("skip-chars")
+                .takes_value(true)
+                .value_name("N")
+                .validator(valid_skip_number)
+                .help("Avoid comparing the first N characters.")
+                .display_order(6))
+        .arg(
+            Arg::new("check_chars")
+                .short('w')
+                .long("check-chars")
+                .takes_value(true)
+                .value_name("N")
+                .validator(valid_char_number)
+                .help("Compare no more than N characters in lines.")
+                .display_order(7))
+        .arg(
+            Arg::new("group")
+                .long("group")
+                .takes_value(true)
+                .min_values(0)
+                .max_values(1)
+                .require_equals(true)
+                .value_name("METHOD")
+                .possible_values(["separate", "prepend", "append", "both"])
+                .default_missing_value("separate")
+                .hide_possible_values(true)
+                .conflicts_with_all(&["count", "repeated", "all_repeated", "unique"])
+                .help("Show all items, delimiting groups with an empty line according to METHOD={separate(default),prepend,append,both}.")
+                .display_order(8))
+        .arg(
+            Arg::new("global")
+                .long("global")
+                .conflicts_with_all(&["all_repeated", "group"])
+                .help("Merge matching lines anywhere in the input, keeping the order in which they were first seen.")
+                .display_order(9))
+        .arg(
+            Arg::new("hash_keys")
+                .long("hash-keys")
+                .requires("global")
+                .help("With --global, remember a hash of each key instead of the whole line, to bound memory use on large inputs.")
+                .display_order(10))
+        .arg(
+            Arg::new("in_file")
+                .takes_value(true)
+                .value_name("INPUT")
+                .default_value("-")
+                .hide_default_value(true))
+        .arg(
+            Arg::new("out_file")
+                .takes_value(true)
+                .value_name("OUTPUT"))
+        .get_matches();
+
+    let in_file = match matches.value_of("in_file").unwrap() {
+        "-" => None,
+        file => Some(file.to_string()),
+    };
+
+    let delimit = match matches
+        .value_of("group")
+        .or_else(|| matches.value_of("all_repeated"))
+    {
+        Some("prepend") => Delimit::Prepend,
+        Some("append") => Delimit::Append,
+        Some("separate") => Delimit::Separate,
+        Some("both") => Delimit::Both,
+        _ => Delimit::None,
+    };
+
+    let repeated = matches.is_present("repeated");
+    let all_repeated = matches.is_present("all_repeated");
+
+    Ok(Config {
+        in_file,
+        out_file: matches.value_of("out_file").map(String::from),
+        count: matches.is_present("count"),
+        output_unique: !(repeated || all_repeated),
+        output_first_repeated: !matches.is_present("unique"),
+        output_later_repeated: all_repeated,
+        grouping: matches.is_present("group"),
+        delimit,
+        global: matches.is_present("global"),
+        hash_keys: matches.is_present("hash_keys"),
+        skip_fields: matches.value_of_t("skip_fields").unwrap_or_default(),
+        skip_chars: matches.value_of_t("skip_chars").unwrap_or_default(),
+        check_chars: matches.value_of_t("check_chars").ok(),
+        ignore_case: matches.is_present("ignore_case"),
+    })
+}
+
+fn valid_field_number(input: &str) -> Result<(), String> {
+    match input.parse::<usize>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("invalid number of fields to skip -- {input}")),
+    }
+}
+
+fn valid_skip_number(input: &str) -> Result<(), String> {
+    match input.parse::<usize>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("invalid number of bytes to skip -- {input}")),
+    }
+}
+
+fn valid_char_number(input: &str) -> Result<(), String> {
+    match input.parse::<usize>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("invalid number of bytes to compare -- {input}")),
+    }
+}
+
+impl Config {
+    fn keeps_every_line(&self) -> bool {
+        self.output_later_repeated || self.grouping
+    }
+
+    // The part of a line that is compared when deciding whether two lines match.
+    // Every output mode goes through this, so -f, -s, -w and -i always apply the same way.
+    fn key<'a>(&self, line: &'a [u8]) -> Cow<'a, [u8]> {
+        let mut start = 0;
+        for _ in 0..self.skip_fields {
+            // With nothing left, skipping any more fields changes nothing.
+            if start == line.len() {
+                break;
+            }
+            while start < line.len() && is_blank(line[start]) {
+                start += 1;
+            }
+            while start < line.len() && !is_blank(line[start]) {
+                start += 1;
+            }
+        }
+        start = line.len().min(start.saturating_add(self.skip_chars));
+
+        let end = match self.check_chars {
+            Some(check_chars) => line.len().min(start.saturating_add(check_chars)),
+            None => line.len(),
+        };
+
+        let key = &line[start..end];
+        if self.ignore_case {
+            Cow::Owned(key.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(key)
+        }
+    }
+}
+
+fn is_blank(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t'
+}
+
+// A run of adjacent matching lines.
+// Only the first line is kept unless every line of the group is going to be printed.
+struct Group {
+    lines: Vec<Vec<u8>>,
+    count: usize,
+}
+
+impl Group {
+    fn new(line: Vec<u8>) -> Self {
+        Group {
+            lines: vec![line],
+            count: 1,
+        }
+    }
+
+    fn first(&self) -> &[u8] {
+        &self.lines[0]
+    }
+}
+
+// The keys already seen by --global, each mapped to the index of its group.
+// With --hash-keys only a hash of each key is kept, so two different keys with the same hash are
+// treated as matching; with 64-bit hashes that is vanishingly unlikely.
+enum Seen {
+    Keys(HashMap<Vec<u8>, usize>),
+    Hashes(HashMap<u64, usize>),
+}
+
+impl Seen {
+    fn new(hash_keys: bool) -> Self {
+        if hash_keys {
+            Seen::Hashes(HashMap::new())
+        } else {
+            Seen::Keys(HashMap::new())
+        }
+    }
+
+    // Returns the index of the group that `key` was first seen with, or records `index` for it.
+    fn get_or_insert(&mut self, key: Cow<[u8]>, index: usize) -> Option<usize> {
+        match self {
+            Seen::Keys(keys) => match keys.get(key.as_ref()) {
+                Some(&seen) => Some(seen),
+                None => {
+                    keys.insert(key.into_owned(), index);
+                    None
+                }
+            },
+            Seen::Hashes(hashes) => {
+                let mut hasher = DefaultHasher::new();
+                key.hash(&mut hasher);
+                match hashes.entry(hasher.finish()) {
+                    Entry::Occupied(seen) => Some(*seen.get()),
+                    Entry::Vacant(entry) => {
+                        entry.insert(index);
+                        None
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    let mut input = open(&config.in_file)
+        .map_err(|e| format!("{}: {e}", config.in_file.as_deref().unwrap_or("-")))?;
+    let mut output = create(&config.out_file)?;
+
+    if config.global {
+        run_global(&config, &mut input, &mut output)?;
+        output.flush()?;
+        return Ok(());
+    }
+
+    let mut line = Vec::new();
+    let mut group: Option<Group> = None;
+    let mut delimited = false;
+
+    while read_line(&mut input, &mut line)? {
+        match &mut group {
+            Some(current) if config.key(current.first()) == config.key(&line) => {
+                current.count += 1;
+                if config.keeps_every_line() {
+                    current.lines.push(mem::take(&mut line));
+                }
+                continue;
+            }
+            Some(current) => write_group(&config, &mut output, current, &mut delimited)?,
+            None => {}
+        }
+        group = Some(Group::new(mem::take(&mut line)));
+    }
+
+    if let Some(current) = group {
+        write_group(&config, &mut output, &current, &mut delimited)?;
+    }
+    if config.grouping && delimited && matches!(config.delimit, Delimit::Append | Delimit::Both) {
+        output.write_all(b"\n")?;
+    }
+
+    output.flush()?;
+    Ok(())
+}
+
+// Without -c, -d or -u a line can be written as soon as its key is new; otherwise every group has to
+// be counted before anything is written.
+fn run_global(
+    config: &Config,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> io::Result<()> {
+    let mut seen = Seen::new(config.hash_keys);
+    let mut line = Vec::new();
+
+    if !config.count && config.output_unique && config.output_first_repeated {
+        while read_line(input, &mut line)? {
+            if seen.get_or_insert(config.key(&line), 0).is_none() {
+                write_line(config, output, &line, 1)?;
+            }
+        }
+        return Ok(());
+    }
+
+    let mut groups: Vec<Group> = Vec::new();
+    while read_line(input, &mut line)? {
+        match seen.get_or_insert(config.key(&line), groups.len()) {
+            Some(index) => groups[index].count += 1,
+            None => groups.push(Group::new(mem::take(&mut line))),
+        }
+    }
+
+    let mut delimited = false;
+    for group in &groups {
+        write_group(config, output, group, &mut delimited)?;
+    }
+    Ok(())
+}
+
+// Reads the next line into `line` without its '\n', returning false at the end of the input.
+fn read_line(input: &mut impl BufRead, line: &mut Vec<u8>) -> io::Result<bool> {
+    line.clear();
+    if input.read_until(b'\n', line)? == 0 {
+        return Ok(false);
+    }
+    if line.last() == Some(&b'\n') {
+        line.pop();
+    }
+    Ok(true)
+}
+
+// `delimited` records whether an earlier group has already been delimited, so that 'separate' and
+// 'append' know whether they are looking at the first group.
+fn write_group(
+    config: &Config,
+    output: &mut impl Write,
+    group: &Group,
+    delimited: &mut bool,
+) -> io::Result<()> {
+    if config.grouping {
+        let delimit = match config.delimit {
+            Delimit::Prepend | Delimit::Both => true,
+            Delimit::Separate | Delimit::Append => *delimited,
+            Delimit::None => false,
+        };
+        if delimit {
+            output.write_all(b"\n")?;
+        }
+        *delimited = true;
+
+        for line in &group.lines {
+            write_line(config, output, line, group.count)?;
+        }
+        return Ok(());
+    }
+
+    if group.count == 1 {
+        if config.output_unique {
+            write_line(config, output, group.first(), group.count)?;
+        }
+        return Ok(());
+    }
+
+    if config.output_later_repeated {
+        let delimit = match config.delimit {
+            Delimit::Prepend => true,
+            Delimit::Separate => *delimited,
+            _ => false,
+        };
+        if delimit {
+            output.write_all(b"\n")?;
+        }
+        *delimited = true;
+    }
+
+    if config.output_first_repeated {
+        write_line(config, output, group.first(), group.count)?;
+    }
+    if config.output_later_repeated {
+        for line in &group.lines[1..] {
+            write_line(config, output, line, group.count)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_line(
+    config: &Config,
+    output: &mut impl Write,
+    line: &[u8],
+    count: usize,
+) -> io::Result<()> {
+    if config.count {
+        write!(output, "{count:>7} ")?;
+    }
+    output.write_all(line)?;
+    output.write_all(b"\n")
+}
+
+fn open(filename: &Option<String>) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+    match filename {
+        None => Ok(Box::new(BufReader::new(io::stdin().lock()))),
+        Some(filename) => Ok(Box::new(BufReader::new(File::open(filename)?))),
+    }
+}
+
+fn create(filename: &Option<String>) -> Result<Box<dyn Write>, Box<dyn Error>> {
+    match filename {
+        None => Ok(Box::new(BufWriter::new(io::stdout().lock()))),
+        Some(filename) => Ok(Box::new(BufWriter::new(File::create(filename)?))),
+    }
+}
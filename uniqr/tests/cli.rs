@@ -0,0 +1,625 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rand::{distributions::Alphanumeric, Rng};
+use std::error::Error;
+use std::fs;
+use tempfile::NamedTempFile;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "uniqr";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const ONE: &str = "tests/inputs/one.txt";
+const TWO: &str = "tests/inputs/two.txt";
+const THREE: &str = "tests/inputs/three.txt";
+const SKIP: &str = "tests/inputs/skip.txt";
+const NO_NEWLINE: &str = "tests/inputs/no-newline.txt";
+const CRLF: &str = "tests/inputs/crlf.txt";
+const FIELDS: &str = "tests/inputs/fields.txt";
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn usage() -> TestResult {
+    for flag in ["-h", "--help"] {
+        Command::cargo_bin(PRG)?
+            .arg(flag)
+            .assert()
+            .stdout(predicate::str::contains("USAGE"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_file() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .arg(&bad)
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read_to_string(expected_file)?;
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin(input_file: &str, args: &[&str], expected_file: &str) -> TestResult {
+    let input = fs::read_to_string(input_file)?;
+    let expected = fs::read_to_string(expected_file)?;
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_outfile(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read_to_string(expected_file)?;
+    let outfile = NamedTempFile::new()?;
+    let outpath = outfile.path().to_str().unwrap();
+
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .arg(outpath)
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(fs::read_to_string(outpath)?, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> TestResult {
+    run(&[EMPTY], "tests/expected/empty.txt.out")
+}
+
+#[test]
+fn empty_stdin() -> TestResult {
+    run_stdin(EMPTY, &[], "tests/expected/empty.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn one() -> TestResult {
+    run(&[ONE], "tests/expected/one.txt.out")
+}
+
+#[test]
+fn one_stdin() -> TestResult {
+    run_stdin(ONE, &["-"], "tests/expected/one.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn two() -> TestResult {
+    run(&[TWO], "tests/expected/two.txt.out")
+}
+
+#[test]
+fn two_stdin() -> TestResult {
+    run_stdin(TWO, &[], "tests/expected/two.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn three() -> TestResult {
+    run(&[THREE], "tests/expected/three.txt.out")
+}
+
+#[test]
+fn three_stdin() -> TestResult {
+    run_stdin(THREE, &[], "tests/expected/three.txt.out")
+}
+
+#[test]
+fn three_outfile() -> TestResult {
+    run_outfile(&[THREE], "tests/expected/three.txt.out")
+}
+
+#[test]
+fn three_stdin_outfile() -> TestResult {
+    let input = fs::read_to_string(THREE)?;
+    let expected = fs::read_to_string("tests/expected/three.txt.out")?;
+    let outfile = NamedTempFile::new()?;
+    let outpath = outfile.path().to_str().unwrap();
+
+    Command::cargo_bin(PRG)?
+        .args(["-", outpath])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(fs::read_to_string(outpath)?, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skip() -> TestResult {
+    run(&[SKIP], "tests/expected/skip.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn no_newline() -> TestResult {
+    run(&[NO_NEWLINE], "tests/expected/no-newline.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn crlf() -> TestResult {
+    run(&[CRLF], "tests/expected/crlf.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_count_and_all_repeated() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-c", "-D", THREE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty_c() -> TestResult {
+    run(&["-c", EMPTY], "tests/expected/empty.txt.c.out")
+}
+
+#[test]
+fn one_c() -> TestResult {
+    run(&["-c", ONE], "tests/expected/one.txt.c.out")
+}
+
+#[test]
+fn one_u() -> TestResult {
+    run(&["-u", ONE], "tests/expected/one.txt.u.out")
+}
+
+#[test]
+fn one_d() -> TestResult {
+    run(&["-d", ONE], "tests/expected/one.txt.d.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn two_c() -> TestResult {
+    run(&["--count", TWO], "tests/expected/two.txt.c.out")
+}
+
+#[test]
+fn two_d() -> TestResult {
+    run(&["--repeated", TWO], "tests/expected/two.txt.d.out")
+}
+
+#[test]
+fn two_big_d() -> TestResult {
+    run(&["-D", TWO], "tests/expected/two.txt.D.out")
+}
+
+#[test]
+fn two_u() -> TestResult {
+    run(&["--unique", TWO], "tests/expected/two.txt.u.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn three_c() -> TestResult {
+    run(&["-c", THREE], "tests/expected/three.txt.c.out")
+}
+
+#[test]
+fn three_c_stdin() -> TestResult {
+    run_stdin(THREE, &["-c"], "tests/expected/three.txt.c.out")
+}
+
+#[test]
+fn three_c_outfile() -> TestResult {
+    run_outfile(&["-c", THREE], "tests/expected/three.txt.c.out")
+}
+
+#[test]
+fn three_d() -> TestResult {
+    run(&["-d", THREE], "tests/expected/three.txt.d.out")
+}
+
+#[test]
+fn three_big_d() -> TestResult {
+    run(&["-D", THREE], "tests/expected/three.txt.D.out")
+}
+
+#[test]
+fn three_big_d_stdin() -> TestResult {
+    run_stdin(THREE, &["-D"], "tests/expected/three.txt.D.out")
+}
+
+#[test]
+fn three_u() -> TestResult {
+    run(&["-u", THREE], "tests/expected/three.txt.u.out")
+}
+
+#[test]
+fn three_c_d() -> TestResult {
+    run(&["-c", "-d", THREE], "tests/expected/three.txt.c.d.out")
+}
+
+#[test]
+fn three_c_u() -> TestResult {
+    run(&["-c", "-u", THREE], "tests/expected/three.txt.c.u.out")
+}
+
+#[test]
+fn three_d_u() -> TestResult {
+    run(&["-d", "-u", THREE], "tests/expected/three.txt.d.u.out")
+}
+
+#[test]
+fn three_big_d_u() -> TestResult {
+    run(&["-D", "-u", THREE], "tests/expected/three.txt.D.u.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn skip_c() -> TestResult {
+    run(&["-c", SKIP], "tests/expected/skip.txt.c.out")
+}
+
+#[test]
+fn no_newline_c() -> TestResult {
+    run(&["-c", NO_NEWLINE], "tests/expected/no-newline.txt.c.out")
+}
+
+#[test]
+fn crlf_big_d() -> TestResult {
+    run(&["-D", CRLF], "tests/expected/crlf.txt.D.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_skip_fields() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-f", "x", FIELDS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid number of fields to skip -- x",
+        ));
+    Ok(())
+}
+
+#[test]
+fn dies_bad_check_chars() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-w", "four", FIELDS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid number of bytes to compare -- four",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fields() -> TestResult {
+    run(&[FIELDS], "tests/expected/fields.txt.out")
+}
+
+#[test]
+fn fields_f1() -> TestResult {
+    run(&["-f", "1", FIELDS], "tests/expected/fields.txt.f1.out")
+}
+
+#[test]
+fn fields_f1_i() -> TestResult {
+    run(
+        &["--skip-fields", "1", "--ignore-case", FIELDS],
+        "tests/expected/fields.txt.f1.i.out",
+    )
+}
+
+#[test]
+fn fields_s20() -> TestResult {
+    run(&["-s", "20", FIELDS], "tests/expected/fields.txt.s20.out")
+}
+
+#[test]
+fn fields_s20_i() -> TestResult {
+    run(
+        &["-s", "20", "-i", FIELDS],
+        "tests/expected/fields.txt.s20.i.out",
+    )
+}
+
+#[test]
+fn fields_s20_i_stdin() -> TestResult {
+    run_stdin(
+        FIELDS,
+        &["-s", "20", "-i"],
+        "tests/expected/fields.txt.s20.i.out",
+    )
+}
+
+#[test]
+fn fields_f1_w4_c() -> TestResult {
+    run(
+        &["-f", "1", "--check-chars", "4", "-c", FIELDS],
+        "tests/expected/fields.txt.f1.w4.c.out",
+    )
+}
+
+#[test]
+fn fields_w19_big_d() -> TestResult {
+    run(
+        &["-w", "19", "-D", FIELDS],
+        "tests/expected/fields.txt.w19.D.out",
+    )
+}
+
+#[test]
+fn fields_f1_s1_i_c() -> TestResult {
+    run(
+        &["-f", "1", "--skip-chars", "1", "-i", "-c", FIELDS],
+        "tests/expected/fields.txt.f1.s1.i.c.out",
+    )
+}
+
+#[test]
+fn fields_f5() -> TestResult {
+    run(&["-f", "5", FIELDS], "tests/expected/fields.txt.f5.out")
+}
+
+#[test]
+fn fields_f_max() -> TestResult {
+    // Every line is all one key, however many fields there are to skip.
+    run(
+        &["-f", "18446744073709551615", "-c", FIELDS],
+        "tests/expected/fields.txt.fmax.c.out",
+    )
+}
+
+#[test]
+fn fields_w0_u() -> TestResult {
+    run(
+        &["-w", "0", "-u", FIELDS],
+        "tests/expected/fields.txt.w0.u.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_group_and_count() -> TestResult {
+    for flag in ["-c", "-d", "-D", "-u"] {
+        Command::cargo_bin(PRG)?
+            .args(["--group", flag, THREE])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+    Ok(())
+}
+
+#[test]
+fn dies_bad_group_method() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--group=sideways", THREE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"sideways\" isn't a valid value"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn three_all_repeated() -> TestResult {
+    run(&["--all-repeated", THREE], "tests/expected/three.txt.D.out")
+}
+
+#[test]
+fn three_all_repeated_none() -> TestResult {
+    run(
+        &["--all-repeated=none", THREE],
+        "tests/expected/three.txt.all-repeated.none.out",
+    )
+}
+
+#[test]
+fn three_all_repeated_prepend() -> TestResult {
+    run(
+        &["--all-repeated=prepend", THREE],
+        "tests/expected/three.txt.all-repeated.prepend.out",
+    )
+}
+
+#[test]
+fn three_all_repeated_separate() -> TestResult {
+    run(
+        &["--all-repeated=separate", THREE],
+        "tests/expected/three.txt.all-repeated.separate.out",
+    )
+}
+
+#[test]
+fn three_all_repeated_separate_u() -> TestResult {
+    run(
+        &["--all-repeated=separate", "-u", THREE],
+        "tests/expected/three.txt.all-repeated.separate.u.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn three_group() -> TestResult {
+    run(
+        &["--group", THREE],
+        "tests/expected/three.txt.group.separate.out",
+    )
+}
+
+#[test]
+fn three_group_prepend() -> TestResult {
+    run(
+        &["--group=prepend", THREE],
+        "tests/expected/three.txt.group.prepend.out",
+    )
+}
+
+#[test]
+fn three_group_append() -> TestResult {
+    run(
+        &["--group=append", THREE],
+        "tests/expected/three.txt.group.append.out",
+    )
+}
+
+#[test]
+fn three_group_both() -> TestResult {
+    run(
+        &["--group=both", THREE],
+        "tests/expected/three.txt.group.both.out",
+    )
+}
+
+#[test]
+fn three_group_both_stdin() -> TestResult {
+    run_stdin(
+        THREE,
+        &["--group=both"],
+        "tests/expected/three.txt.group.both.out",
+    )
+}
+
+#[test]
+fn fields_group_both_f1() -> TestResult {
+    run(
+        &["--group=both", "-f", "1", FIELDS],
+        "tests/expected/fields.txt.group.both.f1.out",
+    )
+}
+
+#[test]
+fn empty_group() -> TestResult {
+    run(
+        &["--group=both", EMPTY],
+        "tests/expected/empty.txt.group.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_hash_keys_without_global() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--hash-keys", THREE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--global"));
+    Ok(())
+}
+
+#[test]
+fn dies_global_and_all_repeated() -> TestResult {
+    for flag in ["-D", "--group"] {
+        Command::cargo_bin(PRG)?
+            .args(["--global", flag, THREE])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn three_global() -> TestResult {
+    run(&["--global", THREE], "tests/expected/three.txt.global.out")
+}
+
+#[test]
+fn three_global_stdin() -> TestResult {
+    run_stdin(THREE, &["--global"], "tests/expected/three.txt.global.out")
+}
+
+#[test]
+fn three_global_c() -> TestResult {
+    run(
+        &["--global", "-c", THREE],
+        "tests/expected/three.txt.global.c.out",
+    )
+}
+
+#[test]
+fn three_global_d() -> TestResult {
+    run(
+        &["--global", "-d", THREE],
+        "tests/expected/three.txt.global.d.out",
+    )
+}
+
+#[test]
+fn three_global_hash_keys() -> TestResult {
+    run(
+        &["--global", "--hash-keys", THREE],
+        "tests/expected/three.txt.global.out",
+    )
+}
+
+#[test]
+fn three_global_hash_keys_c() -> TestResult {
+    run(
+        &["--global", "--hash-keys", "-c", THREE],
+        "tests/expected/three.txt.global.c.out",
+    )
+}
+
+#[test]
+fn skip_global() -> TestResult {
+    run(&["--global", SKIP], "tests/expected/skip.txt.global.out")
+}
+
+#[test]
+fn skip_global_u() -> TestResult {
+    run(
+        &["--global", "-u", SKIP],
+        "tests/expected/skip.txt.global.u.out",
+    )
+}
+
+#[test]
+fn fields_global_f1_i_c() -> TestResult {
+    run(
+        &["--global", "-f", "1", "-i", "-c", FIELDS],
+        "tests/expected/fields.txt.global.f1.i.c.out",
+    )
+}